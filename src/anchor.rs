@@ -0,0 +1,94 @@
+//! Choosing the word a search starts from.
+
+use std::collections::HashSet;
+
+/// Which word is placed first, at the start position, for the rest to be added to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Anchor {
+    /// The first word of the word list.
+    #[default]
+    First,
+    /// The word with the most letters.
+    Longest,
+    /// The word sharing letters with the most other words.
+    Connected,
+    /// Each word in turn, skipping layouts already found from an earlier anchor.
+    Every
+}
+impl Anchor {
+    /// The word indexes to anchor a search at, in order. Ties go to the earlier word.
+    pub fn anchors(&self, word_chars_list: &[Vec<char>]) -> Vec<usize> {
+        let n = word_chars_list.len();
+        let best = |score: &dyn Fn(usize) -> (usize, usize)| {
+            (0..n).fold(0, |best, i| if score(i) > score(best) { i } else { best })
+        };
+        match *self {
+            Anchor::First => vec![0],
+            Anchor::Longest => vec![best(&|i| (word_chars_list[i].len(), 0))],
+            Anchor::Connected => {
+                let letters: Vec<HashSet<char>> = word_chars_list.iter().map(|chars| chars.iter().cloned().collect()).collect();
+                // the number of words it can cross, then the number of ways to cross them
+                vec![best(&|i| {
+                    (0..n).filter(|&j| j != i).fold((0, 0), |(num_words, num_crossings), j| {
+                        let crossings = word_chars_list[j].iter().filter(|c| letters[i].contains(c)).count();
+                        (num_words + if crossings > 0 { 1 } else { 0 }, num_crossings + crossings)
+                    })
+                })]
+            },
+            Anchor::Every => (0..n).collect()
+        }
+    }
+}
+
+use std::fmt::{Display, Formatter, Result};
+impl Display for Anchor {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{}", match *self {
+            Anchor::First => "first",
+            Anchor::Longest => "longest",
+            Anchor::Connected => "connected",
+            Anchor::Every => "every"
+        })
+    }
+}
+use std::str::FromStr;
+impl FromStr for Anchor {
+    type Err = String;
+    fn from_str(s: &str) -> ::std::result::Result<Anchor, String> {
+        match s {
+            "first" => Ok(Anchor::First),
+            "longest" => Ok(Anchor::Longest),
+            "connected" => Ok(Anchor::Connected),
+            "every" => Ok(Anchor::Every),
+            _ => Err("expected first, longest, connected or every".to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(words: &[&str]) -> Vec<Vec<char>> {
+        words.iter().map(|word| word.chars().collect()).collect()
+    }
+
+    #[test]
+    fn anchors() {
+        let words = chars(&["ox", "zebra", "aardvark", "bear", "yak"]);
+        assert_eq!(vec![0], Anchor::First.anchors(&words));
+        assert_eq!(vec![2], Anchor::Longest.anchors(&words));
+        // zebra and bear share letters with every other word but ox, and zebra has more crossings
+        assert_eq!(vec![1], Anchor::Connected.anchors(&words));
+        assert_eq!(vec![0, 1, 2, 3, 4], Anchor::Every.anchors(&words));
+        assert_eq!(vec![0], Anchor::Longest.anchors(&chars(&["ab", "cd"])));
+    }
+
+    #[test]
+    fn parse() {
+        for anchor in &[Anchor::First, Anchor::Longest, Anchor::Connected, Anchor::Every] {
+            assert_eq!(Ok(*anchor), anchor.to_string().parse());
+        }
+        assert!("last".parse::<Anchor>().is_err());
+    }
+}
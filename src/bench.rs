@@ -37,6 +37,27 @@ fn generate_2(b: &mut test::Bencher) {
     })
 }
 
+#[bench]
+fn generate_8_words(b: &mut test::Bencher) {
+    b.iter(|| {
+        let words = vec![
+            "scent",
+            "scarf",
+            "fleet",
+            "tenet",
+            "eerie",
+            "toon",
+            "note",
+            "bent"
+        ];
+        test_generator(words, 0, &|gen| {
+            for cw in gen.iter() {
+                let _ = cw;
+            }
+        });
+    })
+}
+
 /*
 　　　　　　ｕ
 　　　　　　ｎ
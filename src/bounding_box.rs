@@ -1,25 +1,35 @@
+//! The box of cells around a layout.
+
 use placement::Position;
 
+/// The cells from top left to bottom right, inclusive.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct BoundingBox {
+    /// The first row.
     pub top: i8,
+    /// The first col.
     pub left: i8,
+    /// The last row.
     pub bottom: i8,
+    /// The last col.
     pub right: i8
 }
 impl BoundingBox {
+    /// The box from `top`, `left` to `bottom`, `right`.
     pub fn new(top: i8, left: i8, bottom: i8, right: i8) -> BoundingBox {
         BoundingBox {
-            top: top,
-            left: left,
-            bottom: bottom,
-            right: right
+            top,
+            left,
+            bottom,
+            right
         }
     }
+    /// The box around a word of `word_len` letters at `pos`.
     pub fn from_word_pos(word_len: usize, pos: Position) -> BoundingBox {
         let last_pos = pos.letter_pos((word_len - 1) as i8);
         BoundingBox::new(pos.row, pos.col, last_pos.row, last_pos.col)
     }
+    /// The smallest box around both.
     pub fn combine(&self, other: BoundingBox) -> BoundingBox {
         use std::cmp::{min, max};
         BoundingBox::new(
@@ -29,34 +39,42 @@ impl BoundingBox {
             max(self.right, other.right)
         )
     }
+    /// The box with a cell more on every side.
     pub fn expand(&self) -> BoundingBox {
         BoundingBox::new(self.top - 1, self.left - 1, self.bottom + 1, self.right + 1)
     }
+    /// The box with a cell less on every side.
     pub fn contract(&self) -> BoundingBox {
         BoundingBox::new(self.top + 1, self.left + 1, self.bottom - 1, self.right - 1)
     }
+    /// The smallest box around this and a word of `word_len` letters at `pos`.
     pub fn combine_word_pos(&self, word_len: usize, pos: Position) -> BoundingBox {
         self.combine(BoundingBox::from_word_pos(word_len, pos))
     }
+    /// The index of a cell in the box, row by row.
     pub fn row_col(&self, row: i8, col: i8) -> usize {
         let row = (row - self.top) as usize;
         let col = (col - self.left) as usize;
         let width = self.width() as usize;
         width * row + col
     }
+    /// The row and col of the cell at an index, see `row_col`.
     pub fn row_col_inverse(&self, i: usize) -> (i8, i8) {
         let i = i as i16;
         let top = self.top as i16;
         let left = self.left as i16;
-        let width = self.width() as i16;
+        let width = self.width();
         ((i / width + top) as i8, (i % width + left) as i8)
     }
+    /// The number of cols.
     pub fn width(&self) -> i16 {
         self.right as i16 - self.left as i16 + 1
     }
+    /// The number of rows.
     pub fn height(&self) -> i16 {
         self.bottom as i16 - self.top as i16 + 1
     }
+    /// The number of cells.
     pub fn area(&self) -> i16 {
         self.width() * self.height()
     }
@@ -0,0 +1,202 @@
+//! Limits on a run, and how it went.
+
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use observe::CancelToken;
+use seen::SeenStats;
+
+/// Limits on a generator run; None means unlimited.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct Budget {
+    /// How long a run may take, from its first search.
+    pub timeout: Option<Duration>,
+    /// How many crosswords a run may yield.
+    pub max_results: Option<usize>,
+    /// How many words a run may place in total.
+    pub max_nodes: Option<usize>
+}
+
+/// Why a run stopped.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StopReason {
+    /// Every layout was searched.
+    Exhausted,
+    /// The timeout ran out.
+    Timeout,
+    /// It yielded max_results crosswords.
+    MaxResults,
+    /// It placed max_nodes words.
+    MaxNodes,
+    /// Its CancelToken was cancelled.
+    Cancelled
+}
+use std::fmt::{Display, Formatter, Result};
+impl Display for StopReason {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{}", match *self {
+            StopReason::Exhausted => "exhausted",
+            StopReason::Timeout => "timeout",
+            StopReason::MaxResults => "max_results",
+            StopReason::MaxNodes => "max_nodes",
+            StopReason::Cancelled => "cancelled"
+        })
+    }
+}
+
+// why the search turned down a candidate placement
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Rejection {
+    // it runs into or touches a word already placed (can_add_word)
+    Collision,
+    // it makes the layout too big (Filter::by_area)
+    Area,
+    // the layout was already visited, or as the last word, it isn't good enough
+    // (Filter::by_seen)
+    Seen
+}
+
+/// A snapshot of how a run is going.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Stats {
+    /// The time since the run's first search started.
+    pub elapsed: Duration,
+    /// Words placed, each a new node of the search tree.
+    pub nodes: usize,
+    /// Crosswords yielded.
+    pub solutions: usize,
+    /// Placements turned down for running into or touching a word already placed.
+    pub collisions: usize,
+    /// Placements turned down for making the layout too big.
+    pub area_rejections: usize,
+    /// Placements turned down for making a layout already visited, or as the last
+    /// word, one that isn't good enough.
+    pub seen_rejections: usize,
+    /// How much the seen set holds, filled in by `Generator::stats`.
+    pub seen: SeenStats
+}
+impl Display for Stats {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "elapsed: {:.1}s, nodes: {}, solutions: {}, rejected by collision: {}, by area: {}, by seen: {}",
+            self.elapsed.as_secs_f64(), self.nodes, self.solutions, self.collisions, self.area_rejections, self.seen_rejections)?;
+        write!(f, ", seen set: {} ({} bytes)", self.seen.entries, self.seen.bytes)?;
+        if self.seen.dropped > 0 {
+            write!(f, ", not remembered: {}", self.seen.dropped)?;
+        }
+        if self.seen.false_positive_rate > 0.0 {
+            write!(f, ", false positive rate: {:.1e}", self.seen.false_positive_rate)?;
+        }
+        Ok(())
+    }
+}
+
+// reading the clock for every candidate placement is measurable, so only every so often
+const CLOCK_INTERVAL: usize = 256;
+
+// spends a budget over a run. once a limit is hit it stays stopped, and the first
+// limit hit is the reason reported.
+#[derive(Debug)]
+pub(crate) struct Tracker {
+    budget: Budget,
+    start: Mutex<Option<Instant>>,
+    num_checks: AtomicUsize,
+    num_nodes: AtomicUsize,
+    num_results: AtomicUsize,
+    // by Rejection
+    num_rejections: [AtomicUsize; 3],
+    cancel: CancelToken,
+    is_stopped: AtomicBool,
+    stop_reason: Mutex<Option<StopReason>>
+}
+impl Tracker {
+    pub fn new(budget: Budget) -> Tracker {
+        Tracker {
+            budget,
+            start: Mutex::new(None),
+            num_checks: AtomicUsize::new(0),
+            num_nodes: AtomicUsize::new(0),
+            num_results: AtomicUsize::new(0),
+            num_rejections: [AtomicUsize::new(0), AtomicUsize::new(0), AtomicUsize::new(0)],
+            cancel: CancelToken::new(),
+            is_stopped: AtomicBool::new(false),
+            stop_reason: Mutex::new(None)
+        }
+    }
+    pub fn with_cancel_token(self, cancel: CancelToken) -> Tracker {
+        Tracker {
+            cancel,
+            ..self
+        }
+    }
+    pub fn cancel_token(&self) -> CancelToken {
+        self.cancel.clone()
+    }
+    // starts the clock, if it isn't running already
+    pub fn start(&self) {
+        let mut start = self.start.lock().unwrap();
+        if start.is_none() {
+            *start = Some(Instant::now());
+        }
+    }
+    fn stop(&self, reason: StopReason) {
+        let mut stop_reason = self.stop_reason.lock().unwrap();
+        if stop_reason.is_none() {
+            *stop_reason = Some(reason);
+        }
+        self.is_stopped.store(true, Ordering::Relaxed);
+    }
+    pub fn is_stopped(&self) -> bool {
+        if self.cancel.is_cancelled() {
+            self.stop(StopReason::Cancelled);
+        }
+        self.is_stopped.load(Ordering::Relaxed)
+    }
+    pub fn stop_reason(&self) -> StopReason {
+        self.stop_reason.lock().unwrap().unwrap_or(StopReason::Exhausted)
+    }
+    // called for every candidate placement, returns false once the run should stop
+    pub fn check(&self) -> bool {
+        if self.is_stopped() {
+            return false
+        }
+        let num_checks = self.num_checks.fetch_add(1, Ordering::Relaxed);
+        if num_checks.is_multiple_of(CLOCK_INTERVAL) {
+            if let (Some(timeout), Some(start)) = (self.budget.timeout, *self.start.lock().unwrap()) {
+                if start.elapsed() >= timeout {
+                    self.stop(StopReason::Timeout);
+                    return false
+                }
+            }
+        }
+        true
+    }
+    // called for every new node of the search tree
+    pub fn add_node(&self) {
+        let num_nodes = self.num_nodes.fetch_add(1, Ordering::Relaxed) + 1;
+        if self.budget.max_nodes.is_some_and(|max_nodes| num_nodes >= max_nodes) {
+            self.stop(StopReason::MaxNodes);
+        }
+    }
+    pub fn add_result(&self) {
+        let num_results = self.num_results.fetch_add(1, Ordering::Relaxed) + 1;
+        if self.budget.max_results.is_some_and(|max_results| num_results >= max_results) {
+            self.stop(StopReason::MaxResults);
+        }
+    }
+    pub fn reject(&self, rejection: Rejection) {
+        self.num_rejections[rejection as usize].fetch_add(1, Ordering::Relaxed);
+    }
+    pub fn stats(&self) -> Stats {
+        let rejections = |rejection: Rejection| self.num_rejections[rejection as usize].load(Ordering::Relaxed);
+        Stats {
+            elapsed: self.start.lock().unwrap().map_or(Duration::from_secs(0), |start| start.elapsed()),
+            nodes: self.num_nodes.load(Ordering::Relaxed),
+            solutions: self.num_results.load(Ordering::Relaxed),
+            collisions: rejections(Rejection::Collision),
+            area_rejections: rejections(Rejection::Area),
+            seen_rejections: rejections(Rejection::Seen),
+            seen: SeenStats::default()
+        }
+    }
+}
@@ -0,0 +1,410 @@
+//! Saving a search to a file, and reading it back.
+
+use std::fs::{self, File};
+use std::io::prelude::*;
+
+use placement::{Position, START_POSITION};
+use word_placements::{WordPlacements, Dedup};
+use filter::FilterState;
+use search::Cursor;
+use anchor::Anchor;
+use size::SizeLimit;
+use error::CrosswordError;
+use word_list::DEFAULT_WEIGHT;
+
+/// The version of the checkpoint format, on its first line.
+pub const CHECKPOINT_VERSION: u32 = 2;
+
+/// One level of a saved search: the word it placed, and how far through its
+/// candidate placements it got.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FrameState {
+    /// The index of the word placed, and where.
+    pub placement: (usize, Position),
+    /// Whether the word was placed first, rather than on top of the level below.
+    pub is_anchor: bool,
+    /// How far through the level's candidate placements the search got.
+    pub cursor: Cursor,
+    /// Whether a word has been placed on top of this one.
+    pub has_children: bool
+}
+
+/// A saved [`Search`](../search/struct.Search.html): the word placed and the
+/// cursor of each level of the search, the seeds, and what the filter had learned.
+///
+/// Saved as text, one record per line:
+///
+/// ```text
+/// crossword-checkpoint 2
+/// rand 3
+/// num_areas 0
+/// anchor first
+/// dedup exact
+/// partial 0
+/// size - - 0 auto
+/// seed 0
+/// next_seed 0
+/// best_partial 0
+/// word ton
+/// word tok
+/// weights 1 1
+/// frame 0 - 0 1 2 1
+/// frame 1 0,0,V 0 0 0 0
+/// seen 0,0,H 0,0,V
+/// areas 0 9
+/// max_area 0 9
+/// ```
+///
+/// where rand is the version of the hash that orders the search, and a frame is the
+/// index of the word placed and its position (`-` for an anchor word, placed at the
+/// start position or with the locked words), its cursor, and 1 if a word has been
+/// placed on top of it. The weights line holds each word's weight, and is read as
+/// all 1s if it's missing. A seen line is a position per
+/// word (`-` if not placed), and each areas line holds the smallest areas for its
+/// overlap count. Words locked in place get a `locked 1 0,0,V` line each, and a
+/// search with a score saves it as `score area` and the top costs as `costs 9 12`.
+/// Only a search with an exact [`SeenSet`](../seen/enum.SeenSet.html) can be saved
+/// and resumed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkpoint {
+    // rand::RAND_VERSION, as the search order depends on it
+    pub(crate) rand_version: u32,
+    pub(crate) words: Vec<String>,
+    pub(crate) weights: Vec<u32>,
+    pub(crate) num_areas: usize,
+    pub(crate) anchor: Anchor,
+    pub(crate) dedup: Dedup,
+    pub(crate) partial: bool,
+    pub(crate) size: SizeLimit,
+    pub(crate) locked: Vec<(usize, Position)>,
+    pub(crate) score: Option<String>,
+    pub(crate) seed: u64,
+    pub(crate) next_seed: u64,
+    pub(crate) best_partial: u64,
+    pub(crate) frames: Vec<FrameState>,
+    pub(crate) filter: FilterState
+}
+impl Checkpoint {
+    /// A finished search has no frames left, and resumes to nothing.
+    pub fn is_finished(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Parses a checkpoint's text. `source` names it in error messages.
+    pub fn parse(source: &str, text: &str) -> Result<Checkpoint, CrosswordError> {
+        let syntax_error = |line: usize, reason: String| CrosswordError::Checkpoint {
+            source: source.to_string(),
+            line,
+            reason
+        };
+        let mut checkpoint = Checkpoint {
+            rand_version: 0,
+            words: vec![],
+            weights: vec![],
+            num_areas: 0,
+            anchor: Anchor::default(),
+            dedup: Dedup::Exact,
+            partial: false,
+            size: SizeLimit::default(),
+            locked: vec![],
+            score: None,
+            seed: 0,
+            next_seed: 0,
+            best_partial: 0,
+            frames: vec![],
+            filter: FilterState { seen: vec![], overlap_areas: vec![], max_area: (0, 0), costs: vec![] }
+        };
+        let mut has_header = false;
+        for (i, line) in text.lines().enumerate() {
+            let line_number = i + 1;
+            if line.is_empty() {
+                continue
+            }
+            let (key, rest) = match line.find(' ') {
+                Some(i) => (&line[..i], &line[i + 1..]),
+                None => (line, "")
+            };
+            if !has_header {
+                if key != "crossword-checkpoint" {
+                    return Err(syntax_error(line_number, "not a crossword checkpoint".to_string()))
+                }
+                if rest != CHECKPOINT_VERSION.to_string() {
+                    return Err(syntax_error(line_number, format!("unsupported version '{}', expected {}", rest, CHECKPOINT_VERSION)))
+                }
+                has_header = true;
+                continue
+            }
+            let fields: Vec<&str> = rest.split_whitespace().collect();
+            let number = |field: &str| -> Result<u64, CrosswordError> {
+                field.parse::<u64>().map_err(|e| syntax_error(line_number, format!("'{}': {}", field, e)))
+            };
+            let flag = |field: &str| -> Result<bool, CrosswordError> {
+                match field {
+                    "0" => Ok(false),
+                    "1" => Ok(true),
+                    _ => Err(syntax_error(line_number, format!("'{}': expected 0 or 1", field)))
+                }
+            };
+            let position = |field: &str| -> Result<Position, CrosswordError> {
+                field.parse::<Position>().map_err(|e| syntax_error(line_number, e.to_string()))
+            };
+            let expect_fields = |num_fields: usize| -> Result<(), CrosswordError> {
+                if fields.len() == num_fields {
+                    Ok(())
+                } else {
+                    Err(syntax_error(line_number, format!("expected {} values after '{}' but got {}", num_fields, key, fields.len())))
+                }
+            };
+            match key {
+                "rand" => {
+                    expect_fields(1)?;
+                    checkpoint.rand_version = number(fields[0])? as u32;
+                },
+                "num_areas" => {
+                    expect_fields(1)?;
+                    checkpoint.num_areas = number(fields[0])? as usize;
+                },
+                "anchor" => {
+                    expect_fields(1)?;
+                    checkpoint.anchor = fields[0].parse().map_err(|e| syntax_error(line_number, format!("'{}': {}", fields[0], e)))?;
+                },
+                "dedup" => {
+                    expect_fields(1)?;
+                    checkpoint.dedup = fields[0].parse().map_err(|e| syntax_error(line_number, format!("'{}': {}", fields[0], e)))?;
+                },
+                "partial" => {
+                    expect_fields(1)?;
+                    checkpoint.partial = flag(fields[0])?;
+                },
+                "size" => {
+                    expect_fields(4)?;
+                    let max_size = |field: &str| -> Result<Option<usize>, CrosswordError> {
+                        if field == "-" { Ok(None) } else { number(field).map(|n| Some(n as usize)) }
+                    };
+                    checkpoint.size = SizeLimit {
+                        max_width: max_size(fields[0])?,
+                        max_height: max_size(fields[1])?,
+                        exact: flag(fields[2])?,
+                        orientation: fields[3].parse().map_err(|e| syntax_error(line_number, format!("'{}': {}", fields[3], e)))?
+                    };
+                },
+                "seed" => {
+                    expect_fields(1)?;
+                    checkpoint.seed = number(fields[0])?;
+                },
+                "next_seed" => {
+                    expect_fields(1)?;
+                    checkpoint.next_seed = number(fields[0])?;
+                },
+                "best_partial" => {
+                    expect_fields(1)?;
+                    checkpoint.best_partial = number(fields[0])?;
+                },
+                // words may contain spaces, so take the rest of the line as is
+                "word" => checkpoint.words.push(rest.to_string()),
+                "score" => checkpoint.score = Some(rest.to_string()),
+                "weights" => {
+                    expect_fields(checkpoint.words.len())?;
+                    checkpoint.weights = fields.iter()
+                        .map(|&field| field.parse::<u32>().map_err(|e| syntax_error(line_number, format!("'{}': {}", field, e))))
+                        .collect::<Result<Vec<_>, _>>()?;
+                },
+                "locked" => {
+                    expect_fields(2)?;
+                    let word_index = number(fields[0])? as usize;
+                    if word_index >= checkpoint.words.len() {
+                        return Err(syntax_error(line_number, format!("no word {}", word_index)))
+                    }
+                    checkpoint.locked.push((word_index, position(fields[1])?));
+                },
+                "frame" => {
+                    expect_fields(6)?;
+                    let word_index = number(fields[0])? as usize;
+                    if word_index >= checkpoint.words.len() {
+                        return Err(syntax_error(line_number, format!("no word {}", word_index)))
+                    }
+                    let (pos, is_anchor) = match fields[1] {
+                        "-" => (START_POSITION, true),
+                        pos => (position(pos)?, false)
+                    };
+                    checkpoint.frames.push(FrameState {
+                        placement: (word_index, pos),
+                        is_anchor,
+                        cursor: Cursor {
+                            word: number(fields[2])? as usize,
+                            letter: number(fields[3])? as usize,
+                            word_char: number(fields[4])? as usize
+                        },
+                        has_children: flag(fields[5])?
+                    });
+                },
+                "seen" => {
+                    expect_fields(checkpoint.words.len())?;
+                    let placements = fields.iter()
+                        .map(|&field| if field == "-" { Ok(None) } else { position(field).map(Some) })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    checkpoint.filter.seen.push(WordPlacements::from_vec(placements));
+                },
+                "areas" => {
+                    let areas = fields.iter()
+                        .map(|&field| field.parse::<i16>().map_err(|e| syntax_error(line_number, format!("'{}': {}", field, e))))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    checkpoint.filter.overlap_areas.push(areas);
+                },
+                "costs" => {
+                    checkpoint.filter.costs = fields.iter()
+                        .map(|&field| field.parse::<f64>().map_err(|e| syntax_error(line_number, format!("'{}': {}", field, e))))
+                        .collect::<Result<Vec<_>, _>>()?;
+                },
+                "max_area" => {
+                    expect_fields(2)?;
+                    let overlaps = fields[0].parse::<i8>().map_err(|e| syntax_error(line_number, format!("'{}': {}", fields[0], e)))?;
+                    let area = fields[1].parse::<i16>().map_err(|e| syntax_error(line_number, format!("'{}': {}", fields[1], e)))?;
+                    checkpoint.filter.max_area = (overlaps, area);
+                },
+                _ => return Err(syntax_error(line_number, format!("unknown record '{}'", key)))
+            }
+        }
+        if !has_header {
+            return Err(syntax_error(1, "not a crossword checkpoint".to_string()))
+        }
+        if checkpoint.weights.is_empty() {
+            checkpoint.weights = vec![DEFAULT_WEIGHT; checkpoint.words.len()];
+        }
+        Ok(checkpoint)
+    }
+
+    /// Reads and parses a checkpoint file.
+    pub fn load(path: &str) -> Result<Checkpoint, CrosswordError> {
+        let mut text = String::new();
+        File::open(path).and_then(|mut file| file.read_to_string(&mut text)).map_err(|e| CrosswordError::Io {
+            path: path.to_string(),
+            reason: e.to_string()
+        })?;
+        Checkpoint::parse(path, &text)
+    }
+
+    /// Writes to a temporary file first, so a crash while saving keeps the old checkpoint.
+    pub fn save(&self, path: &str) -> Result<(), CrosswordError> {
+        let tmp_path = format!("{}.tmp", path);
+        File::create(&tmp_path)
+            .and_then(|mut file| {
+                write!(file, "{}", self)?;
+                file.sync_all()
+            })
+            .and_then(|_| fs::rename(&tmp_path, path))
+            .map_err(|e| CrosswordError::Io {
+                path: path.to_string(),
+                reason: e.to_string()
+            })
+    }
+}
+
+use std::fmt::{Display, Formatter, Result as FmtResult};
+impl Display for Checkpoint {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        writeln!(f, "crossword-checkpoint {}", CHECKPOINT_VERSION)?;
+        writeln!(f, "rand {}", self.rand_version)?;
+        writeln!(f, "num_areas {}", self.num_areas)?;
+        writeln!(f, "anchor {}", self.anchor)?;
+        writeln!(f, "dedup {}", self.dedup)?;
+        writeln!(f, "partial {}", if self.partial { 1 } else { 0 })?;
+        let max_size = |max_size: Option<usize>| max_size.map_or("-".to_string(), |max_size| max_size.to_string());
+        writeln!(f, "size {} {} {} {}", max_size(self.size.max_width), max_size(self.size.max_height),
+            if self.size.exact { 1 } else { 0 }, self.size.orientation)?;
+        if let Some(ref score) = self.score {
+            writeln!(f, "score {}", score)?;
+        }
+        writeln!(f, "seed {}", self.seed)?;
+        writeln!(f, "next_seed {}", self.next_seed)?;
+        writeln!(f, "best_partial {}", self.best_partial)?;
+        for word in &self.words {
+            writeln!(f, "word {}", word)?;
+        }
+        write!(f, "weights")?;
+        for weight in &self.weights {
+            write!(f, " {}", weight)?;
+        }
+        writeln!(f)?;
+        for &(word_index, pos) in &self.locked {
+            writeln!(f, "locked {} {}", word_index, pos)?;
+        }
+        for frame in &self.frames {
+            let (word_index, pos) = frame.placement;
+            if frame.is_anchor {
+                write!(f, "frame {} -", word_index)?;
+            } else {
+                write!(f, "frame {} {}", word_index, pos)?;
+            }
+            writeln!(f, " {} {} {} {}", frame.cursor.word, frame.cursor.letter, frame.cursor.word_char,
+                if frame.has_children { 1 } else { 0 })?;
+        }
+        for placements in &self.filter.seen {
+            write!(f, "seen")?;
+            for placement in placements.as_slice() {
+                match *placement {
+                    Some(pos) => write!(f, " {}", pos)?,
+                    None => write!(f, " -")?
+                }
+            }
+            writeln!(f)?;
+        }
+        for areas in &self.filter.overlap_areas {
+            write!(f, "areas")?;
+            for area in areas {
+                write!(f, " {}", area)?;
+            }
+            writeln!(f)?;
+        }
+        if !self.filter.costs.is_empty() {
+            write!(f, "costs")?;
+            for cost in &self.filter.costs {
+                write!(f, " {}", cost)?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f, "max_area {} {}", self.filter.max_area.0, self.filter.max_area.1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_errors() {
+        match Checkpoint::parse("test", "crossword-checkpoint 2\nseed x\n") {
+            Err(CrosswordError::Checkpoint { line, .. }) => assert_eq!(2, line),
+            other => panic!("expected a Checkpoint error, got {:?}", other)
+        }
+        assert!(Checkpoint::parse("test", "crossword-checkpoint 1\n").is_err());
+        assert!(Checkpoint::parse("test", "seed 1\n").is_err());
+        assert!(Checkpoint::parse("test", "").is_err());
+        assert!(Checkpoint::parse("test", "crossword-checkpoint 2\nword ton\nframe 3 0,0,V 0 0 0 0\n").is_err());
+        assert!(Checkpoint::parse("test", "crossword-checkpoint 2\nword ton\nseen - -\n").is_err());
+        assert!(Checkpoint::parse("test", "crossword-checkpoint 2\nword ton\nweights 1 2\n").is_err());
+        assert!(Checkpoint::parse("test", "crossword-checkpoint 2\nword ton\nweights -1\n").is_err());
+        assert!(Checkpoint::parse("test", "crossword-checkpoint 2\nanchor last\n").is_err());
+        // every 0 or 1 flag is read the same way, and says which line it's on
+        for text in &["crossword-checkpoint 2\npartial yes\n", "crossword-checkpoint 2\nword ton\nframe 0 - 0 0 0 2\n"] {
+            match Checkpoint::parse("test", text) {
+                Err(CrosswordError::Checkpoint { line, reason, .. }) => {
+                    assert_eq!(text.lines().count(), line);
+                    assert!(reason.ends_with("expected 0 or 1"), "{}", reason);
+                },
+                other => panic!("expected a Checkpoint error, got {:?}", other)
+            }
+        }
+        assert!(Checkpoint::parse("test", "crossword-checkpoint 2\nsize 5 5 0\n").is_err());
+        assert!(Checkpoint::parse("test", "crossword-checkpoint 2\nsize 5 x 0 auto\n").is_err());
+        assert!(Checkpoint::parse("test", "crossword-checkpoint 2\nsize 5 - 2 auto\n").is_err());
+    }
+
+    #[test]
+    fn words_with_spaces() {
+        let checkpoint = Checkpoint::parse("test", "crossword-checkpoint 2\nword new york\nword ok\n").unwrap();
+        assert_eq!(vec!["new york".to_string(), "ok".to_string()], checkpoint.words);
+        assert_eq!(vec![1, 1], checkpoint.weights);
+        assert_eq!(checkpoint, Checkpoint::parse("test", &checkpoint.to_string()).unwrap());
+    }
+}
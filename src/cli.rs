@@ -0,0 +1,592 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use crossword::{Config, Position, Strategy, SeenSet};
+use crossword::config::parse_duration;
+
+pub const USAGE: &str = "\
+Usage: crossword [COMMAND] [OPTIONS] [< words.txt]
+
+Commands:
+  generate    generate crosswords from the words on stdin (default)
+  render      render a crossword from words and --placements
+  validate    check that words and --placements form a valid crossword
+  stats       generate crosswords and print a summary instead of layouts
+  optimize    improve a layout by moving its words around, printing each better one;
+              starts from --placements, or from each word added where it fits best
+
+Options:
+  -n, --num-areas <N>      keep only the N smallest areas per overlap count, or with
+                           --score, the N lowest scores (0: all) [default: 0]
+      --score <SPEC>       rank layouts by a weighted sum of area, overlaps, density,
+                           squareness and perimeter, lower is better, e.g. area,overlaps:10
+  -s, --seed <N>           seed for the search order (0: input order) [default: 0]
+  -t, --iters <N>          number of interleaved searches [default: 1]
+  -j, --threads <N>        search on N threads; crosswords come in no particular order
+                           and --iters is ignored [default: 1]
+      --partial            also yield layouts that leave out words that can't be placed,
+                           whenever they place at least as much weight as any before
+      --no-partial         don't, whatever the config file says
+  -a, --anchor <STRATEGY>  which word to place first: first, longest, connected (shares
+                           letters with the most words) or every (each word in turn,
+                           skipping layouts found from an earlier one) [default: first]
+      --max-width <N>      keep only layouts at most N letters wide
+      --max-height <N>     keep only layouts at most N letters tall
+      --exact-size         keep only layouts exactly --max-width by --max-height
+      --no-exact-size      keep smaller layouts too, whatever the config file says
+      --orientation <auto|fixed>
+                           auto: layouts may be turned on their side to fit, and are
+                           printed portrait and landscape; fixed: width is across
+                           [default: auto]
+      --strategy <SPEC>    how to search: dfs (depth-first, visiting every layout),
+                           beam[:WIDTH] (keep the best WIDTH layouts as each word is
+                           added) or lds[:N] (follow the best layouts first, straying
+                           from them at most N times); beam and lds rank layouts by
+                           --score, or by area then overlaps [default: dfs]
+      --dedup <exact|canonical>
+                           canonical: skip layouts that are only another one moved or
+                           transposed (only moved, with --orientation fixed and a frame
+                           that isn't square); exact: keep them for the orientation variety
+                           [default: canonical with --anchor every, else exact]
+      --seen <SPEC>        how to remember visited layouts: exact, fingerprint (64-bit
+                           hashes) or bloom[:RATE] (a bloom filter that skips about
+                           RATE of new layouts as seen) [default: exact]
+      --max-memory <SIZE>  stop remembering visited layouts once the seen set takes
+                           SIZE bytes, e.g. 512M; bloom filters take all of it
+                           [default for bloom: 16M]
+      --timeout <SECS>     stop generating after SECS seconds, e.g. 90 or 2.5
+      --max-results <N>    stop generating after N crosswords
+      --max-nodes <N>      stop generating after placing N words in total
+                           (optimize: after N moves) [default for optimize: 20000]
+      --checkpoint <FILE>  save the search to FILE every --checkpoint-interval and when it
+                           stops, so it can be resumed
+      --checkpoint-interval <SECS>
+                           how often to save the --checkpoint [default: 60]
+      --resume <FILE>      continue the search saved in FILE, with the same words and
+                           weights, --num-areas, --anchor, --dedup, --partial and size
+                           limits (and a fresh budget)
+  -L, --lock <WORD=POS>    pin WORD at POS, a row,col,dir triple like 3,0,H, and generate
+                           the rest around it (repeatable)
+  -p, --placements <LIST>  word positions as row,col,dir triples, e.g. 3,0,H,0,2,V
+  -w, --words <FILE>       read words from FILE instead of stdin (repeatable)
+      --progress           print the nodes, solutions and rejected placements so far
+                           to stderr every second
+  -b, --batch              generate or stats: run each word list separately, where lists
+                           are separated by blank lines on stdin or given as --words files
+      --fold-case          lowercase words before matching them up
+      --lossy              replace invalid UTF-8 in word lists instead of rejecting them
+  -c, --config <FILE>      read settings from FILE [default: crossword.conf, if present]
+  -P, --profile <NAME>     use the settings of profile NAME from the config file
+  -h, --help               print this message
+
+Word lists have one word per line, optionally followed by a tab-separated clue
+and priority weight (word<TAB>clue<TAB>weight). Lines starting with # are comments.
+Words need 2 to 60 letters.
+Words are trimmed and accents are composed, so duplicates can be reported and skipped.
+
+Config files hold `key = value` lines for num_areas, seed, iters, threads,
+partial (true or false), anchor, max_width, max_height, exact_size (true or false),
+orientation, score, strategy, dedup, seen, max_memory, timeout, max_results and
+max_nodes. Settings at the top apply to every profile, and `[profile.NAME]` sections
+hold named profiles. Command line options override both.
+
+Crosswords are written to stdout as YAML; errors and warnings go to stderr.
+
+Exit codes:
+  0  success
+  1  no crossword is possible (or validate: the layout is invalid; optimize: the best
+     layout leaves words out)
+  2  invalid input: bad arguments, word lists or placements
+  3  the search stopped before it finished: a --timeout or --max-nodes budget ran out,
+     or it was cancelled
+";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Command {
+    Generate,
+    Render,
+    Validate,
+    Stats,
+    Optimize,
+    Help
+}
+impl FromStr for Command {
+    type Err = CliError;
+    fn from_str(s: &str) -> Result<Command, CliError> {
+        match s {
+            "generate" => Ok(Command::Generate),
+            "render" => Ok(Command::Render),
+            "validate" => Ok(Command::Validate),
+            "stats" => Ok(Command::Stats),
+            "optimize" => Ok(Command::Optimize),
+            "help" => Ok(Command::Help),
+            _ => Err(CliError::UnknownCommand(s.to_string()))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    pub config: Config,
+    pub config_path: Option<String>,
+    pub profile: Option<String>,
+    pub placements: Option<String>,
+    pub word_files: Vec<String>,
+    pub batch: bool,
+    pub fold_case: bool,
+    pub lossy: bool,
+    pub progress: bool,
+    pub checkpoint: Option<String>,
+    pub checkpoint_interval: Duration,
+    pub resume: Option<String>,
+    pub locks: Vec<(String, Position)>
+}
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            config: Config::default(),
+            config_path: None,
+            profile: None,
+            placements: None,
+            word_files: vec![],
+            batch: false,
+            fold_case: false,
+            lossy: false,
+            progress: false,
+            checkpoint: None,
+            checkpoint_interval: Duration::from_secs(60),
+            resume: None,
+            locks: vec![]
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cli {
+    pub command: Command,
+    pub options: Options
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CliError {
+    UnknownCommand(String),
+    UnknownOption(String),
+    MissingValue(String),
+    UnexpectedValue { option: String, value: String },
+    InvalidValue { option: String, value: String, reason: String },
+    UnexpectedArgument(String),
+    MissingOption { command: Command, option: String },
+    UnsupportedOption { command: Command, option: String },
+    ConflictingOptions(String, String)
+}
+
+use std::fmt::{Display, Formatter, Result as FmtResult};
+impl Display for CliError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match *self {
+            CliError::UnknownCommand(ref cmd) => write!(f, "unknown command '{}'", cmd),
+            CliError::UnknownOption(ref opt) => write!(f, "unknown option '{}'", opt),
+            CliError::MissingValue(ref opt) => write!(f, "option '{}' requires a value", opt),
+            CliError::UnexpectedValue { ref option, ref value } => {
+                write!(f, "option '{}' doesn't take a value, got '{}'", option, value)
+            },
+            CliError::InvalidValue { ref option, ref value, ref reason } => {
+                write!(f, "invalid value '{}' for '{}': {}", value, option, reason)
+            },
+            CliError::UnexpectedArgument(ref arg) => write!(f, "unexpected argument '{}'", arg),
+            CliError::MissingOption { command, ref option } => {
+                write!(f, "'{}' requires option '{}'", command, option)
+            },
+            CliError::UnsupportedOption { command, ref option } => {
+                write!(f, "'{}' does not support option '{}'", command, option)
+            },
+            CliError::ConflictingOptions(ref option1, ref option2) => {
+                write!(f, "options '{}' and '{}' can't be used together", option1, option2)
+            }
+        }
+    }
+}
+impl Display for Command {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "{}", match *self {
+            Command::Generate => "generate",
+            Command::Render => "render",
+            Command::Validate => "validate",
+            Command::Stats => "stats",
+            Command::Optimize => "optimize",
+            Command::Help => "help"
+        })
+    }
+}
+
+// the options that take no value. --partial and --exact-size may be given one.
+const FLAGS: [&str; 9] = ["-h", "--help", "-b", "--batch", "--fold-case", "--lossy", "--progress", "--no-partial", "--no-exact-size"];
+
+// args excludes the program name
+pub fn parse(args: &[String]) -> Result<Cli, CliError> {
+    parse_onto(args, Options::default())
+}
+
+// parses args over options that were already set, e.g. from a config file
+pub fn parse_onto(args: &[String], options: Options) -> Result<Cli, CliError> {
+    let mut args = args.iter().peekable();
+    let command = match args.peek() {
+        Some(arg) if !arg.starts_with('-') => args.next().unwrap().parse()?,
+        _ => Command::Generate
+    };
+    let mut options = options;
+    let mut is_help = command == Command::Help;
+    while let Some(arg) = args.next() {
+        if !arg.starts_with('-') || arg == "-" {
+            return Err(CliError::UnexpectedArgument(arg.clone()))
+        }
+        // accept both `--seed 3` and `--seed=3`
+        let (option, inline_value) = match arg.find('=') {
+            Some(i) if arg.starts_with("--") => (&arg[..i], Some(arg[i + 1..].to_string())),
+            _ => (&arg[..], None)
+        };
+        if let Some(ref value) = inline_value {
+            if FLAGS.contains(&option) {
+                return Err(CliError::UnexpectedValue { option: option.to_string(), value: value.clone() })
+            }
+        }
+        match option {
+            "-h" | "--help" => {
+                is_help = true;
+                continue
+            },
+            "-b" | "--batch" => {
+                options.batch = true;
+                continue
+            },
+            "--fold-case" => {
+                options.fold_case = true;
+                continue
+            },
+            "--lossy" => {
+                options.lossy = true;
+                continue
+            },
+            "--progress" => {
+                options.progress = true;
+                continue
+            },
+            // --partial=false and --exact-size=false are read like the config file's
+            "--partial" if inline_value.is_none() => {
+                options.config.partial = true;
+                continue
+            },
+            "--no-partial" => {
+                options.config.partial = false;
+                continue
+            },
+            "--exact-size" if inline_value.is_none() => {
+                options.config.size.exact = true;
+                continue
+            },
+            "--no-exact-size" => {
+                options.config.size.exact = false;
+                continue
+            },
+            _ => {}
+        }
+        let mut value = || -> Result<String, CliError> {
+            match inline_value.clone() {
+                Some(value) => Ok(value),
+                None => args.next().cloned().ok_or_else(|| CliError::MissingValue(option.to_string()))
+            }
+        };
+        let config_key = match option {
+            "-n" | "--num-areas" => Some("num_areas"),
+            "-s" | "--seed" => Some("seed"),
+            "-t" | "--iters" => Some("iters"),
+            "-j" | "--threads" => Some("threads"),
+            "--partial" => Some("partial"),
+            "-a" | "--anchor" => Some("anchor"),
+            "--max-width" => Some("max_width"),
+            "--max-height" => Some("max_height"),
+            "--exact-size" => Some("exact_size"),
+            "--orientation" => Some("orientation"),
+            "--score" => Some("score"),
+            "--strategy" => Some("strategy"),
+            "--dedup" => Some("dedup"),
+            "--seen" => Some("seen"),
+            "--max-memory" => Some("max_memory"),
+            "--timeout" => Some("timeout"),
+            "--max-results" => Some("max_results"),
+            "--max-nodes" => Some("max_nodes"),
+            _ => None
+        };
+        if let Some(key) = config_key {
+            let value = value()?;
+            options.config.set(key, &value).map_err(|reason| CliError::InvalidValue {
+                option: option.to_string(),
+                value,
+                reason
+            })?;
+            continue
+        }
+        match option {
+            "-c" | "--config" => options.config_path = Some(value()?),
+            "-P" | "--profile" => options.profile = Some(value()?),
+            "-p" | "--placements" => options.placements = Some(value()?),
+            "-w" | "--words" => options.word_files.push(value()?),
+            "--checkpoint" => options.checkpoint = Some(value()?),
+            "--resume" => options.resume = Some(value()?),
+            "-L" | "--lock" => {
+                let value = value()?;
+                let lock = parse_lock(&value).map_err(|reason| CliError::InvalidValue {
+                    option: option.to_string(),
+                    value: value.clone(),
+                    reason
+                })?;
+                options.locks.push(lock);
+            },
+            "--checkpoint-interval" => {
+                let value = value()?;
+                options.checkpoint_interval = parse_duration(&value).map_err(|reason| CliError::InvalidValue {
+                    option: option.to_string(),
+                    value,
+                    reason
+                })?;
+            },
+            _ => return Err(CliError::UnknownOption(option.to_string()))
+        }
+    }
+    if is_help {
+        return Ok(Cli { command: Command::Help, options })
+    }
+    match command {
+        Command::Render | Command::Validate if options.placements.is_none() => {
+            Err(CliError::MissingOption { command, option: "--placements".to_string() })
+        },
+        Command::Render | Command::Validate if options.batch => {
+            Err(CliError::UnsupportedOption { command, option: "--batch".to_string() })
+        },
+        Command::Render | Command::Validate if !options.locks.is_empty() => {
+            Err(CliError::UnsupportedOption { command, option: "--lock".to_string() })
+        },
+        Command::Render | Command::Validate if options.checkpoint.is_some() || options.resume.is_some() => {
+            let option = if options.checkpoint.is_some() { "--checkpoint" } else { "--resume" };
+            Err(CliError::UnsupportedOption { command, option: option.to_string() })
+        },
+        Command::Optimize if options.batch || options.checkpoint.is_some() || options.resume.is_some() => {
+            let option = if options.batch { "--batch" } else if options.checkpoint.is_some() { "--checkpoint" } else { "--resume" };
+            Err(CliError::UnsupportedOption { command, option: option.to_string() })
+        },
+        Command::Optimize if options.placements.is_some() && !options.locks.is_empty() => {
+            Err(CliError::ConflictingOptions("--lock".to_string(), "--placements".to_string()))
+        },
+        _ => check_checkpoint(options).map(|options| Cli { command, options })
+    }
+}
+
+// WORD=ROW,COL,DIR. the word is split off at the last =, so it may contain one
+fn parse_lock(value: &str) -> Result<(String, Position), String> {
+    let i = value.rfind('=').ok_or_else(|| "expected WORD=ROW,COL,DIR".to_string())?;
+    let pos = value[i + 1..].parse::<Position>().map_err(|e| e.to_string())?;
+    Ok((value[..i].to_string(), pos))
+}
+
+// a checkpoint saves a single depth-first search, so not a batch or several searches
+// at once, and its seen set, which only an exact one can give back. the other
+// strategies search on one thread.
+fn check_checkpoint(options: Options) -> Result<Options, CliError> {
+    if options.config.strategy != Strategy::Dfs {
+        let conflict = if options.checkpoint.is_some() {
+            Some("--checkpoint")
+        } else if options.resume.is_some() {
+            Some("--resume")
+        } else if options.config.num_threads > 1 {
+            Some("--threads")
+        } else if options.config.num_iters > 1 {
+            Some("--iters")
+        } else {
+            None
+        };
+        if let Some(conflict) = conflict {
+            return Err(CliError::ConflictingOptions("--strategy".to_string(), conflict.to_string()))
+        }
+    }
+    let option = match (&options.checkpoint, &options.resume) {
+        (&Some(_), _) => "--checkpoint",
+        (_, &Some(_)) => "--resume",
+        _ => return Ok(options)
+    };
+    let conflict = if options.batch {
+        Some("--batch")
+    } else if options.config.num_threads > 1 {
+        Some("--threads")
+    } else if options.config.num_iters > 1 {
+        Some("--iters")
+    } else if options.config.seen != SeenSet::Exact {
+        Some("--seen")
+    } else {
+        None
+    };
+    match conflict {
+        Some(conflict) => Err(CliError::ConflictingOptions(option.to_string(), conflict.to_string())),
+        None => Ok(options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crossword::{Anchor, SizeLimit, Orientation, Direction, Dedup};
+
+    fn parse_str(args: &str) -> Result<Cli, CliError> {
+        let args: Vec<String> = args.split_whitespace().map(|s| s.to_string()).collect();
+        parse(&args)
+    }
+
+    #[test]
+    fn defaults_to_generate() {
+        let cli = parse_str("").unwrap();
+        assert_eq!(Command::Generate, cli.command);
+        assert_eq!(Options::default(), cli.options);
+
+        let cli = parse_str("-n 1 -s 2 -t 3 -j 4").unwrap();
+        assert_eq!(Command::Generate, cli.command);
+        assert_eq!(1, cli.options.config.num_areas);
+        assert_eq!(2, cli.options.config.seed);
+        assert_eq!(3, cli.options.config.num_iters);
+        assert_eq!(4, cli.options.config.num_threads);
+    }
+
+    #[test]
+    fn long_options() {
+        let cli = parse_str("stats --num-areas=4 --seed 5 --anchor=every").unwrap();
+        assert_eq!(Command::Stats, cli.command);
+        assert_eq!(4, cli.options.config.num_areas);
+        assert_eq!(5, cli.options.config.seed);
+        assert_eq!(Anchor::Every, cli.options.config.anchor);
+
+        let cli = parse_str("--words a.tsv -w b.tsv").unwrap();
+        assert_eq!(vec!["a.tsv".to_string(), "b.tsv".to_string()], cli.options.word_files);
+
+        assert!(parse_str("stats --batch").unwrap().options.batch);
+        let options = parse_str("--fold-case --lossy --partial --progress").unwrap().options;
+        assert!(options.fold_case && options.lossy && options.config.partial && options.progress);
+
+        let options = parse_str("--timeout 1.5 --max-results 3 --max-nodes=1000").unwrap().options;
+        assert_eq!(Some(Duration::from_millis(1500)), options.config.budget.timeout);
+        assert_eq!(Some(3), options.config.budget.max_results);
+        assert_eq!(Some(1000), options.config.budget.max_nodes);
+
+        let options = parse_str("--max-width 13 --max-height=9 --exact-size --orientation fixed").unwrap().options;
+        assert_eq!(SizeLimit { max_width: Some(13), max_height: Some(9), exact: true, orientation: Orientation::Fixed }, options.config.size);
+        let options = parse_str("--score area:2,overlaps").unwrap().options;
+        assert_eq!(Some("area:2,overlaps".to_string()), options.config.score.map(|score| score.to_string()));
+        assert!(parse_str("--score volume").is_err());
+        assert_eq!(Strategy::Lds { discrepancies: 3 }, parse_str("--strategy lds:3").unwrap().options.config.strategy);
+        assert!(parse_str("--strategy bfs").is_err());
+        assert_eq!(Some(Dedup::Exact), parse_str("--dedup exact").unwrap().options.config.dedup);
+        assert!(parse_str("--dedup rotated").is_err());
+        let options = parse_str("--seen bloom:0.01 --max-memory 1G").unwrap().options;
+        assert_eq!(SeenSet::Bloom { false_positive_rate: 0.01 }, options.config.seen);
+        assert_eq!(Some(1 << 30), options.config.max_memory);
+        assert!(parse_str("--max-memory lots").is_err());
+
+        let options = parse_str("--checkpoint a.ckpt --checkpoint-interval=5 --resume b.ckpt").unwrap().options;
+        assert_eq!(Some("a.ckpt".to_string()), options.checkpoint);
+        assert_eq!(Duration::from_secs(5), options.checkpoint_interval);
+        assert_eq!(Some("b.ckpt".to_string()), options.resume);
+
+        let options = parse_str("--lock theme=3,0,H -L a=b=0,2,V").unwrap().options;
+        assert_eq!(vec![
+            ("theme".to_string(), Position { row: 3, col: 0, dir: Direction::Horizontal }),
+            ("a=b".to_string(), Position { row: 0, col: 2, dir: Direction::Vertical })
+        ], options.locks);
+        assert!(parse_str("--lock theme").is_err());
+        assert!(parse_str("--lock theme=3,0").is_err());
+
+        let options = parse_str("-c my.conf --profile newspaper").unwrap().options;
+        assert_eq!(Some("my.conf".to_string()), options.config_path);
+        assert_eq!(Some("newspaper".to_string()), options.profile);
+    }
+
+    #[test]
+    fn overrides() {
+        let args: Vec<String> = vec!["--seed".to_string(), "9".to_string()];
+        let base = Options { config: Config::new(3, 7), ..Options::default() };
+        let cli = parse_onto(&args, base).unwrap();
+        assert_eq!(Config::new(3, 9), cli.options.config);
+
+        // the flags turn a profile's setting off as well as on
+        let parse_over = |args: &str, on: bool| {
+            let args: Vec<String> = args.split_whitespace().map(|s| s.to_string()).collect();
+            let mut base = Options::default();
+            base.config.partial = on;
+            base.config.size.exact = on;
+            let config = parse_onto(&args, base).unwrap().options.config;
+            (config.partial, config.size.exact)
+        };
+        assert_eq!((true, true), parse_over("--partial --exact-size", false));
+        assert_eq!((true, true), parse_over("--partial=true --exact-size=true", false));
+        assert_eq!((false, false), parse_over("--no-partial --no-exact-size", true));
+        assert_eq!((false, false), parse_over("--partial=false --exact-size=false", true));
+        assert_eq!((true, true), parse_over("", true));
+        assert!(parse_str("--partial=maybe").is_err());
+    }
+
+    #[test]
+    fn help() {
+        assert_eq!(Command::Help, parse_str("--help").unwrap().command);
+        assert_eq!(Command::Help, parse_str("render -h").unwrap().command);
+        assert_eq!(Command::Help, parse_str("help").unwrap().command);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(Err(CliError::UnknownCommand("draw".to_string())), parse_str("draw"));
+        assert_eq!(Err(CliError::UnknownOption("--frobnicate".to_string())), parse_str("--frobnicate"));
+        assert_eq!(Err(CliError::MissingValue("--seed".to_string())), parse_str("--seed"));
+        assert_eq!(
+            Err(CliError::UnexpectedValue { option: "--batch".to_string(), value: "foo".to_string() }),
+            parse_str("--batch=foo")
+        );
+        assert!(parse_str("--no-partial=true").is_err());
+        assert!(parse_str("--help=").is_err());
+        assert_eq!(Err(CliError::UnexpectedArgument("extra".to_string())), parse_str("generate extra"));
+        match parse_str("-t x") {
+            Err(CliError::InvalidValue { option, value, .. }) => {
+                assert_eq!("-t", option);
+                assert_eq!("x", value);
+            },
+            other => panic!("expected InvalidValue, got {:?}", other)
+        }
+        assert!(parse_str("--timeout -1").is_err());
+        assert!(parse_str("--max-results 0").is_err());
+        assert_eq!(
+            Err(CliError::MissingOption { command: Command::Validate, option: "--placements".to_string() }),
+            parse_str("validate")
+        );
+        assert_eq!(
+            Err(CliError::UnsupportedOption { command: Command::Render, option: "--batch".to_string() }),
+            parse_str("render -p 0,0,H -b")
+        );
+        assert_eq!(
+            Err(CliError::ConflictingOptions("--checkpoint".to_string(), "--threads".to_string())),
+            parse_str("--checkpoint a.ckpt -j 2")
+        );
+        assert!(parse_str("--resume a.ckpt -b").is_err());
+        assert_eq!(
+            Err(CliError::ConflictingOptions("--strategy".to_string(), "--threads".to_string())),
+            parse_str("--strategy beam -j 2")
+        );
+        assert!(parse_str("--strategy lds --checkpoint a.ckpt").is_err());
+        assert_eq!(
+            Err(CliError::ConflictingOptions("--resume".to_string(), "--seen".to_string())),
+            parse_str("--resume a.ckpt --seen fingerprint")
+        );
+        assert!(parse_str("validate -p 0,0,H --resume a.ckpt").is_err());
+        assert_eq!(Command::Optimize, parse_str("optimize -p 0,0,H --max-nodes 100").unwrap().command);
+        assert_eq!(
+            Err(CliError::UnsupportedOption { command: Command::Optimize, option: "--batch".to_string() }),
+            parse_str("optimize -b")
+        );
+        assert!(parse_str("optimize -p 0,0,H -L a=0,0,H").is_err());
+    }
+}
@@ -0,0 +1,489 @@
+//! Generator settings, and the config files that hold them.
+
+use std::fs::File;
+use std::io::prelude::*;
+use std::str::FromStr;
+use std::time::Duration;
+
+use budget::Budget;
+use anchor::Anchor;
+use size::{SizeLimit, Orientation};
+use score::Weighted;
+use strategy::Strategy;
+use word_placements::Dedup;
+use seen::SeenSet;
+
+/// The config file read if no other is given.
+pub const DEFAULT_CONFIG_PATH: &str = "crossword.conf";
+
+/// Generator settings, as set by a config file profile and/or command line flags.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    /// The profile the settings were read from, if any.
+    pub profile: Option<String>,
+    /// Keep only the num_areas smallest areas per overlap count, or with a score, the
+    /// num_areas lowest costs; 0 keeps every layout.
+    pub num_areas: usize,
+    /// The seed for the search order; 0 searches in word list order.
+    pub seed: u64,
+    /// The number of interleaved searches.
+    pub num_iters: usize,
+    /// More than 1 searches on that many threads, instead of interleaving iters.
+    pub num_threads: usize,
+    /// Also yield layouts that leave out words that can't be placed.
+    pub partial: bool,
+    /// Which word, or words, each search starts from.
+    pub anchor: Anchor,
+    /// The frame layouts have to fit.
+    pub size: SizeLimit,
+    /// num_areas keeps the top layouts by this instead of by area per overlap count.
+    pub score: Option<Weighted>,
+    /// How the layouts are searched.
+    pub strategy: Strategy,
+    /// How layouts are deduplicated; None picks by the anchor, see `get_dedup`.
+    pub dedup: Option<Dedup>,
+    /// How the search remembers the layouts it has visited.
+    pub seen: SeenSet,
+    /// The most bytes the seen set may take.
+    pub max_memory: Option<usize>,
+    /// Limits on how long a run goes on.
+    pub budget: Budget
+}
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            profile: None,
+            num_areas: 0,
+            seed: 0,
+            num_iters: 1,
+            num_threads: 1,
+            partial: false,
+            anchor: Anchor::default(),
+            size: SizeLimit::default(),
+            score: None,
+            strategy: Strategy::default(),
+            dedup: None,
+            seen: SeenSet::default(),
+            max_memory: None,
+            budget: Budget::default()
+        }
+    }
+}
+impl Config {
+    /// The default settings, but for `num_areas` and `seed`.
+    pub fn new(num_areas: usize, seed: u64) -> Config {
+        Config {
+            num_areas,
+            seed,
+            ..Config::default()
+        }
+    }
+    /// How layouts are deduplicated, if not set: canonical with anchor every, which
+    /// would otherwise find each layout again from each anchor, and exact otherwise.
+    pub fn get_dedup(&self) -> Dedup {
+        match (self.dedup, self.anchor) {
+            (Some(dedup), _) => dedup,
+            (None, Anchor::Every) => Dedup::Canonical,
+            (None, _) => Dedup::Exact
+        }
+    }
+    /// Sets a setting by its config file key, returning why the value is invalid if it is.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "num_areas" => self.num_areas = parse_value(value)?,
+            "seed" => self.seed = parse_value(value)?,
+            "iters" => self.num_iters = parse_positive(value)?,
+            "threads" => self.num_threads = parse_positive(value)?,
+            "partial" => self.partial = parse_value(value)?,
+            "anchor" => self.anchor = parse_value(value)?,
+            "max_width" => self.size.max_width = Some(parse_positive(value)?),
+            "max_height" => self.size.max_height = Some(parse_positive(value)?),
+            "exact_size" => self.size.exact = parse_value(value)?,
+            "orientation" => self.size.orientation = parse_value(value)?,
+            "score" => self.score = Some(parse_value(value)?),
+            "strategy" => self.strategy = parse_value(value)?,
+            "dedup" => self.dedup = Some(parse_value(value)?),
+            "seen" => self.seen = parse_value(value)?,
+            "max_memory" => self.max_memory = Some(parse_memory(value)?),
+            "timeout" => self.budget.timeout = Some(parse_duration(value)?),
+            "max_results" => self.budget.max_results = Some(parse_positive(value)?),
+            "max_nodes" => self.budget.max_nodes = Some(parse_positive(value)?),
+            _ => return Err(format!("unknown setting '{}'", key))
+        }
+        Ok(())
+    }
+}
+
+fn parse_value<T: FromStr>(value: &str) -> Result<T, String> where T::Err: ToString {
+    value.parse::<T>().map_err(|e| e.to_string())
+}
+fn parse_positive(value: &str) -> Result<usize, String> {
+    match parse_value(value)? {
+        0 => Err("must be at least 1".to_string()),
+        n => Ok(n)
+    }
+}
+/// Parses a duration in seconds, e.g. 90 or 2.5.
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let secs: f64 = parse_value(value)?;
+    if !(secs >= 0.0 && secs.is_finite()) {
+        return Err("must be a non-negative number of seconds".to_string())
+    }
+    Ok(Duration::from_secs_f64(secs))
+}
+/// Parses a memory size in bytes, or with a K, M or G suffix for KiB, MiB or GiB, e.g. 512M.
+pub fn parse_memory(value: &str) -> Result<usize, String> {
+    let (number, shift) = match value.chars().last() {
+        Some('K') | Some('k') => (&value[..value.len() - 1], 10),
+        Some('M') | Some('m') => (&value[..value.len() - 1], 20),
+        Some('G') | Some('g') => (&value[..value.len() - 1], 30),
+        _ => (value, 0)
+    };
+    let number: usize = parse_positive(number.trim())?;
+    number.checked_mul(1 << shift).ok_or_else(|| "too large".to_string())
+}
+
+use std::fmt::{Display, Formatter, Result as FmtResult};
+// YAML lines, as echoed in the Generator header
+impl Display for Config {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        if let Some(ref profile) = self.profile {
+            writeln!(f, "profile: {}", profile)?;
+        }
+        writeln!(f, "num_areas: {}", self.num_areas)?;
+        writeln!(f, "seed: {}", self.seed)?;
+        writeln!(f, "iters: {}", self.num_iters)?;
+        if self.num_threads != 1 {
+            writeln!(f, "threads: {}", self.num_threads)?;
+        }
+        if self.partial {
+            writeln!(f, "partial: true")?;
+        }
+        if self.anchor != Anchor::default() {
+            writeln!(f, "anchor: {}", self.anchor)?;
+        }
+        if let Some(max_width) = self.size.max_width {
+            writeln!(f, "max_width: {}", max_width)?;
+        }
+        if let Some(max_height) = self.size.max_height {
+            writeln!(f, "max_height: {}", max_height)?;
+        }
+        if self.size.exact {
+            writeln!(f, "exact_size: true")?;
+        }
+        if self.size.orientation != Orientation::default() {
+            writeln!(f, "orientation: {}", self.size.orientation)?;
+        }
+        if let Some(ref score) = self.score {
+            writeln!(f, "score: {}", score)?;
+        }
+        if self.strategy != Strategy::default() {
+            writeln!(f, "strategy: {}", self.strategy)?;
+        }
+        if let Some(dedup) = self.dedup {
+            writeln!(f, "dedup: {}", dedup)?;
+        }
+        if self.seen != SeenSet::default() {
+            writeln!(f, "seen: {}", self.seen)?;
+        }
+        if let Some(max_memory) = self.max_memory {
+            writeln!(f, "max_memory: {}", max_memory)?;
+        }
+        if let Some(timeout) = self.budget.timeout {
+            writeln!(f, "timeout: {}", timeout.as_secs_f64())?;
+        }
+        if let Some(max_results) = self.budget.max_results {
+            writeln!(f, "max_results: {}", max_results)?;
+        }
+        if let Some(max_nodes) = self.budget.max_nodes {
+            writeln!(f, "max_nodes: {}", max_nodes)?;
+        }
+        write!(f, "")
+    }
+}
+
+/// Why a config file couldn't be used. `source` is the path it was read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    /// It couldn't be read.
+    Io {
+        /// The path.
+        source: String,
+        /// The IO error.
+        reason: String
+    },
+    /// A line isn't a `[profile]` header, a `key = value` setting or a comment.
+    Syntax {
+        /// The path.
+        source: String,
+        /// The line number, from 1.
+        line: usize,
+        /// What's wrong with the line.
+        reason: String
+    },
+    /// A setting's key is unknown, or its value is invalid.
+    InvalidSetting {
+        /// The path.
+        source: String,
+        /// The line number, from 1.
+        line: usize,
+        /// The setting's key.
+        key: String,
+        /// The setting's value.
+        value: String,
+        /// What's wrong with it.
+        reason: String
+    },
+    /// The profile asked for isn't in the file.
+    UnknownProfile {
+        /// The path.
+        source: String,
+        /// The profile asked for.
+        profile: String,
+        /// The profiles the file has.
+        profiles: Vec<String>
+    }
+}
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match *self {
+            ConfigError::Io { ref source, ref reason } => write!(f, "{}: {}", source, reason),
+            ConfigError::Syntax { ref source, line, ref reason } => write!(f, "{}:{}: {}", source, line, reason),
+            ConfigError::InvalidSetting { ref source, line, ref key, ref value, ref reason } => {
+                write!(f, "{}:{}: invalid value '{}' for '{}': {}", source, line, value, key, reason)
+            },
+            ConfigError::UnknownProfile { ref source, ref profile, ref profiles } => {
+                write!(f, "{}: no profile '{}' (profiles: {})", source, profile,
+                    if profiles.is_empty() { "none".to_string() } else { profiles.join(", ") })
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Setting {
+    line: usize,
+    key: String,
+    value: String
+}
+
+/// INI-like: `key = value` lines, with `[profile.NAME]` sections holding named
+/// profiles. Settings before the first section apply to every profile.
+/// Blank lines and lines starting with # or ; are ignored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigFile {
+    source: String,
+    defaults: Vec<Setting>,
+    profiles: Vec<(String, Vec<Setting>)>
+}
+impl ConfigFile {
+    /// Parses a config file's text. `source` names it in error messages.
+    pub fn parse(source: &str, text: &str) -> Result<ConfigFile, ConfigError> {
+        let syntax_error = |line: usize, reason: String| ConfigError::Syntax {
+            source: source.to_string(),
+            line,
+            reason
+        };
+        let mut config_file = ConfigFile {
+            source: source.to_string(),
+            defaults: vec![],
+            profiles: vec![]
+        };
+        let mut profile: Option<usize> = None;
+        for (i, line) in text.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue
+            }
+            if line.starts_with('[') {
+                if !line.ends_with(']') {
+                    return Err(syntax_error(line_number, format!("unclosed section header '{}'", line)))
+                }
+                let section = line[1..line.len() - 1].trim();
+                let name = section.strip_prefix("profile.").unwrap_or(section);
+                if name.is_empty() {
+                    return Err(syntax_error(line_number, "empty profile name".to_string()))
+                }
+                let index = match config_file.profiles.iter().position(|(other, _)| other == name) {
+                    Some(index) => index,
+                    None => {
+                        config_file.profiles.push((name.to_string(), vec![]));
+                        config_file.profiles.len() - 1
+                    }
+                };
+                profile = Some(index);
+                continue
+            }
+            let (key, value) = match line.find('=') {
+                Some(i) => (line[..i].trim(), line[i + 1..].trim()),
+                None => return Err(syntax_error(line_number, format!("expected key = value but got '{}'", line)))
+            };
+            let value = if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+                &value[1..value.len() - 1]
+            } else {
+                value
+            };
+            // check the setting now, so errors point at the file rather than a profile
+            if let Err(reason) = Config::default().set(key, value) {
+                return Err(ConfigError::InvalidSetting {
+                    source: source.to_string(),
+                    line: line_number,
+                    key: key.to_string(),
+                    value: value.to_string(),
+                    reason
+                })
+            }
+            let setting = Setting { line: line_number, key: key.to_string(), value: value.to_string() };
+            match profile {
+                Some(index) => config_file.profiles[index].1.push(setting),
+                None => config_file.defaults.push(setting)
+            }
+        }
+        Ok(config_file)
+    }
+
+    /// Reads and parses a config file.
+    pub fn load(path: &str) -> Result<ConfigFile, ConfigError> {
+        let mut text = String::new();
+        File::open(path).and_then(|mut file| file.read_to_string(&mut text)).map_err(|e| ConfigError::Io {
+            source: path.to_string(),
+            reason: e.to_string()
+        })?;
+        ConfigFile::parse(path, &text)
+    }
+
+    /// The names of the file's profiles, in the order they appear.
+    pub fn profiles(&self) -> Vec<&str> {
+        self.profiles.iter().map(|(name, _)| &name[..]).collect()
+    }
+
+    /// Applies the defaults, then the named profile's settings, on top of `config`.
+    pub fn apply(&self, config: &mut Config, profile: Option<&str>) -> Result<(), ConfigError> {
+        let profile_settings = match profile {
+            Some(name) => match self.profiles.iter().find(|&(other, _)| other == name) {
+                Some((_, settings)) => &settings[..],
+                None => return Err(ConfigError::UnknownProfile {
+                    source: self.source.clone(),
+                    profile: name.to_string(),
+                    profiles: self.profiles().iter().map(|name| name.to_string()).collect()
+                })
+            },
+            None => &[]
+        };
+        for setting in self.defaults.iter().chain(profile_settings) {
+            config.set(&setting.key, &setting.value).map_err(|reason| ConfigError::InvalidSetting {
+                source: self.source.clone(),
+                line: setting.line,
+                key: setting.key.clone(),
+                value: setting.value.clone(),
+                reason
+            })?;
+        }
+        config.profile = profile.map(|name| name.to_string());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = "
+# shared by every profile
+seed = 7
+iters = 2
+
+[profile.newspaper]
+num_areas = 3
+timeout = 2.5
+threads = 4
+anchor = longest
+
+; a bare section name works too
+[quick]
+max_results = \"1\"
+strategy = beam:8
+seen = bloom:0.01
+max_memory = 64M
+
+[profile.weekend]
+score = area, squareness:50
+max_width = 15
+max_height = 15
+exact_size = true
+orientation = fixed
+dedup = canonical
+";
+
+    #[test]
+    fn apply_profiles() {
+        let config_file = ConfigFile::parse("test", CONFIG).unwrap();
+        assert_eq!(vec!["newspaper", "quick", "weekend"], config_file.profiles());
+
+        let mut config = Config::default();
+        config_file.apply(&mut config, None).unwrap();
+        assert_eq!(Config { seed: 7, num_iters: 2, ..Config::default() }, config);
+
+        let mut config = Config::default();
+        config_file.apply(&mut config, Some("newspaper")).unwrap();
+        assert_eq!(Some("newspaper".to_string()), config.profile);
+        assert_eq!(3, config.num_areas);
+        assert_eq!(Some(Duration::from_millis(2500)), config.budget.timeout);
+        assert_eq!(4, config.num_threads);
+        assert_eq!(Anchor::Longest, config.anchor);
+        assert_eq!(None, config.budget.max_results);
+
+        let mut config = Config::default();
+        config_file.apply(&mut config, Some("quick")).unwrap();
+        assert_eq!(Some(1), config.budget.max_results);
+        assert_eq!(Strategy::Beam { width: 8 }, config.strategy);
+        assert_eq!(SeenSet::Bloom { false_positive_rate: 0.01 }, config.seen);
+        assert_eq!(Some(64 << 20), config.max_memory);
+
+        let mut config = Config::default();
+        config_file.apply(&mut config, Some("weekend")).unwrap();
+        assert_eq!(SizeLimit { max_width: Some(15), max_height: Some(15), exact: true, orientation: Orientation::Fixed }, config.size);
+        assert_eq!(Dedup::Canonical, config.get_dedup());
+        assert_eq!("area,squareness:50", config.score.unwrap().to_string());
+
+        match config_file.apply(&mut Config::default(), Some("magazine")) {
+            Err(ConfigError::UnknownProfile { profiles, .. }) => assert_eq!(vec!["newspaper", "quick", "weekend"], profiles),
+            other => panic!("expected UnknownProfile, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn errors() {
+        match ConfigFile::parse("test", "seed = 1\nnum_areas = lots\n") {
+            Err(ConfigError::InvalidSetting { line, key, .. }) => {
+                assert_eq!(2, line);
+                assert_eq!("num_areas", key);
+            },
+            other => panic!("expected InvalidSetting, got {:?}", other)
+        }
+        assert!(ConfigFile::parse("test", "colour = blue").is_err());
+        assert!(ConfigFile::parse("test", "anchor = last").is_err());
+        assert!(ConfigFile::parse("test", "max_width = 0").is_err());
+        assert!(ConfigFile::parse("test", "orientation = sideways").is_err());
+        assert!(ConfigFile::parse("test", "strategy = beam:0").is_err());
+        assert!(ConfigFile::parse("test", "dedup = rotated").is_err());
+        assert!(ConfigFile::parse("test", "seen = bloom:2").is_err());
+        assert!(ConfigFile::parse("test", "max_memory = 0").is_err());
+        assert!(ConfigFile::parse("test", "max_memory = 12T").is_err());
+        assert!(ConfigFile::parse("test", "[newspaper").is_err());
+        assert!(ConfigFile::parse("test", "seed").is_err());
+    }
+
+    #[test]
+    fn display() {
+        let mut config = Config::new(1, 2);
+        config.profile = Some("quick".to_string());
+        config.budget.max_nodes = Some(10);
+        config.budget.timeout = Some(Duration::from_millis(1500));
+        assert_eq!("profile: quick\nnum_areas: 1\nseed: 2\niters: 1\ntimeout: 1.5\nmax_nodes: 10\n", format!("{}", config));
+        config.seen = SeenSet::Fingerprint;
+        config.max_memory = Some(parse_memory("2k").unwrap());
+        assert!(format!("{}", config).contains("seen: fingerprint\nmax_memory: 2048\n"));
+    }
+}
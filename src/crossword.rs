@@ -1,11 +1,20 @@
+//! A layout of words.
+
+use std::collections::{HashMap, HashSet};
+
 use placement::Position;
 use bounding_box::BoundingBox;
 
 use grid::Grid;
 use word_placements::WordPlacements;
+use word_list::check_words;
+use error::CrosswordError;
+use size::Orientation;
 
+/// A layout of words, built up one word at a time. Equal when the positions are equal.
 #[derive(Debug, Clone)]
 pub struct Crossword {
+    /// Where each word is placed.
     pub positions: WordPlacements,
     grid: Grid
 }
@@ -16,56 +25,178 @@ impl PartialEq for Crossword {
 }
 impl Eq for Crossword {}
 impl Crossword {
+    /// An empty layout of `num_words` words, none of them placed yet.
     pub fn new(num_words: usize) -> Crossword {
         Crossword {
             positions: WordPlacements::new(num_words),
             grid: Grid::new(BoundingBox::new(0, 0, 0, 0))
         }
     }
+    /// Places `words[i]` at `positions[i]`, in order. The layout may still be
+    /// invalid, see `validate`.
+    pub fn from_positions<S: AsRef<str>>(words: &[S], positions: &[Position]) -> ::std::result::Result<Crossword, CrosswordError> {
+        Crossword::build(words, positions, false)
+    }
+    /// Like `from_positions`, but also rejects the first word that collides with or
+    /// touches the words before it, then the first word that isn't joined to the
+    /// first one through crossings.
+    pub fn validate<S: AsRef<str>>(words: &[S], positions: &[Position]) -> ::std::result::Result<Crossword, CrosswordError> {
+        let crossword = Crossword::build(words, positions, true)?;
+        match crossword.first_disconnected(|i| words[i].as_ref().chars().count()) {
+            Some(index) => {
+                let word = words[index].as_ref().to_string();
+                Err(CrosswordError::Disconnected { index, word, pos: positions[index] })
+            },
+            None => Ok(crossword)
+        }
+    }
+    fn build<S: AsRef<str>>(words: &[S], positions: &[Position], check_collisions: bool) -> ::std::result::Result<Crossword, CrosswordError> {
+        check_words(words)?;
+        if words.len() != positions.len() {
+            return Err(CrosswordError::PositionCount { num_words: words.len(), num_positions: positions.len() })
+        }
+        let mut crossword = Crossword::new(words.len());
+        for (word_index, (word, &pos)) in words.iter().zip(positions).enumerate() {
+            let word = word.as_ref();
+            let word_len = word.chars().count();
+            if !pos.fits(word_len) {
+                return Err(CrosswordError::OutOfBounds { index: word_index, word: word.to_string(), pos })
+            }
+            crossword = crossword.set(word, word_len, word_index, pos);
+            if check_collisions && !crossword.is_valid() {
+                return Err(CrosswordError::Collision { index: word_index, word: word.to_string(), pos })
+            }
+        }
+        Ok(crossword)
+    }
+    /// Whether the word can go at `pos` without colliding with or touching the words
+    /// placed so far.
     pub fn can_add_word(&self, word: &str, word_len: usize, pos: Position) -> bool {
         self.grid.can_add_word(word, word_len, pos)
     }
+    /// A copy of the layout with the word at `word_index` placed at `pos`. It isn't
+    /// checked: see `can_add_word` and `is_valid`.
     pub fn set(&self, word: &str, word_len: usize, word_index: usize, pos: Position) -> Crossword {
         Crossword {
             positions: self.positions.set(word_index, pos),
             grid: self.grid.set(word, word_len, pos)
         }
     }
+    /// The layout with a word taken out, built in one go rather than a word at a time.
+    /// `words` gives each word and its length by index.
+    pub fn without<'a, F: Fn(usize) -> (&'a str, usize)>(&self, word_index: usize, words: F) -> Crossword {
+        let placed: Vec<_> = self.positions.as_slice().iter().enumerate()
+            .filter_map(|(i, &opt_pos)| match opt_pos {
+                Some(pos) if i != word_index => {
+                    let (word, word_len) = words(i);
+                    Some((word, word_len, pos))
+                },
+                _ => None
+            })
+            .collect();
+        let mut positions = self.positions.as_slice().to_vec();
+        positions[word_index] = None;
+        Crossword {
+            positions: WordPlacements::from_vec(positions),
+            grid: Grid::from_words(&placed)
+        }
+    }
+    /// The first placed word that isn't joined to the first placed word through
+    /// crossings, directly or through other words. `word_len` gives each word's
+    /// length by index.
+    pub fn first_disconnected<F: Fn(usize) -> usize>(&self, word_len: F) -> Option<usize> {
+        let placed: Vec<(usize, Position)> = self.positions.as_slice().iter().enumerate()
+            .filter_map(|(i, &opt_pos)| opt_pos.map(|pos| (i, pos)))
+            .collect();
+        let mut words_at: HashMap<(i8, i8), Vec<usize>> = HashMap::new();
+        for &(i, pos) in &placed {
+            for offset in 0..word_len(i) {
+                let letter_pos = pos.letter_pos(offset as i8);
+                words_at.entry((letter_pos.row, letter_pos.col)).or_default().push(i);
+            }
+        }
+        let mut reached: HashSet<usize> = HashSet::new();
+        let mut stack: Vec<usize> = placed.iter().take(1).map(|&(i, _)| i).collect();
+        while let Some(i) = stack.pop() {
+            if !reached.insert(i) {
+                continue
+            }
+            let pos = self.positions.get(i).unwrap();
+            for offset in 0..word_len(i) {
+                let letter_pos = pos.letter_pos(offset as i8);
+                stack.extend(words_at[&(letter_pos.row, letter_pos.col)].iter().filter(|j| !reached.contains(j)));
+            }
+        }
+        placed.iter().map(|&(i, _)| i).find(|i| !reached.contains(i))
+    }
+    /// The smallest box around the placed words.
     pub fn bounding_box(&self) -> BoundingBox {
         self.grid.bb.contract()
     }
 
+    /// Whether no word collides with or touches another where they don't cross.
     pub fn is_valid(&self) -> bool {
         self.grid.is_valid
     }
+    /// The number of cells where two words cross.
     pub fn num_overlaps(&self) -> i8 {
         self.grid.num_overlaps
     }
+    /// The letters no word crosses yet, each with its cell and the direction of the
+    /// word it's in.
     pub fn letters(&self) -> &Vec<(char, Position)> {
         &self.grid.letters
     }
+    /// The number of cells with a letter, counting crossings once.
+    pub fn num_letters(&self) -> usize {
+        self.grid.num_letters()
+    }
 }
 use std::fmt::{Display, Formatter, Result};
 impl Display for Crossword {
     fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{}", self.oriented(Orientation::Auto))
+    }
+}
+impl Crossword {
+    /// The layout to display in an orientation. With a fixed orientation, it's
+    /// displayed as laid out instead of portrait first.
+    pub fn oriented(&self, orientation: Orientation) -> Oriented<'_> {
+        Oriented {
+            crossword: self,
+            orientation
+        }
+    }
+}
+/// Displays a layout as YAML in an orientation, see `Crossword::oriented`.
+pub struct Oriented<'a> {
+    crossword: &'a Crossword,
+    orientation: Orientation
+}
+impl<'a> Display for Oriented<'a> {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        let crossword = self.crossword;
+        let is_fixed = self.orientation == Orientation::Fixed;
         // portrait by default
-        let bb = self.bounding_box();
+        let bb = crossword.bounding_box();
         let (width, height) = (bb.width(), bb.height());
-        let is_landscape = width > height;
+        let is_landscape = width > height && !is_fixed;
         let (width, height) = if is_landscape { (height, width) } else { (width, height) };
 
-        writeln!(f, "[{}]:", self.positions)?;
+        writeln!(f, "[{}]:", crossword.positions)?;
         writeln!(f, "  width: {}", width)?;
         writeln!(f, "  height: {}", height)?;
         writeln!(f, "  area: {}", width * height)?;
-        writeln!(f, "  overlaps: {}", self.num_overlaps())?;
+        writeln!(f, "  overlaps: {}", crossword.num_overlaps())?;
 
-        if is_landscape {
-            write_grid(f, "portrait", self.grid.iter_cols())?;
-            write_grid(f, "landscape", self.grid.iter_rows())
+        if is_fixed {
+            write_grid(f, "grid", crossword.grid.iter_rows())
+        } else if is_landscape {
+            write_grid(f, "portrait", crossword.grid.iter_cols())?;
+            write_grid(f, "landscape", crossword.grid.iter_rows())
         } else {
-            write_grid(f, "portrait", self.grid.iter_rows())?;
-            write_grid(f, "landscape", self.grid.iter_cols())
+            write_grid(f, "portrait", crossword.grid.iter_rows())?;
+            write_grid(f, "landscape", crossword.grid.iter_cols())
         }
     }
 }
@@ -78,11 +209,11 @@ fn write_grid<T, U>(f: &mut Formatter, key_name: &str, iter: T) -> Result
         if let Some(cell) = entry {
             write!(f, "{}", cell)?;
         } else {
-            writeln!(f, "")?;
+            writeln!(f)?;
             write!(f, "    ")?;
         }
     }
-    writeln!(f, "")
+    writeln!(f)
 }
 
 #[cfg(test)]
@@ -140,6 +271,9 @@ pub mod tests {
         let crossword = make_hello_world();
         let bb = crossword.bounding_box();
         assert_eq!(BoundingBox { top: 0, left: 0, bottom: 4, right: 4 }, bb);
+        // not stretched to the origin
+        let crossword = make_crossword(vec![make_hello()]);
+        assert_eq!(BoundingBox { top: 3, left: 0, bottom: 3, right: 4 }, crossword.bounding_box());
     }
 
     #[test]
@@ -152,6 +286,7 @@ pub mod tests {
     fn letters() {
         let crossword = make_crossword(vec![make_hello()]);
         assert_eq!(5, crossword.letters().len());
+        assert_eq!(9, make_hello_world().num_letters());
 
         let crossword = make_hello_world();
         for l in crossword.letters() {
@@ -171,12 +306,39 @@ pub mod tests {
         assert_eq!(expected, format!("{}", crossword));
     }
 
+    #[test]
+    fn display_fixed() {
+        let crossword = make_crossword(vec![("hello", Position { row: 0, col: 0, dir: Horizontal })]);
+        assert!(format!("{}", crossword).contains("  width: 1\n  height: 5\n"));
+        let expected = "[0,0,H]:\n  width: 5\n  height: 1\n  area: 5\n  overlaps: 0\n  grid: |\n    .\n    hello\n";
+        assert_eq!(expected, format!("{}", crossword.oriented(Orientation::Fixed)));
+    }
+
     #[test]
     fn is_valid() {
         let crossword = make_hello_world();
         assert!(crossword.is_valid());
     }
 
+    #[test]
+    fn without() {
+        let word_positions = vec![make_hello(), make_world(), make_bye()];
+        let words = |i: usize| (word_positions[i].0, word_positions[i].0.len());
+        // the same as setting the other words in turn
+        let crossword = make_crossword(word_positions.clone());
+        let (hello, bye) = (word_positions[0], word_positions[2]);
+        let expected = Crossword::new(3).set(hello.0, 5, 0, hello.1).set(bye.0, 3, 2, bye.1);
+        let rest = crossword.without(1, words);
+        assert_eq!(expected, rest);
+        assert_eq!(expected.letters(), rest.letters());
+        assert_eq!((expected.is_valid(), expected.num_overlaps()), (rest.is_valid(), rest.num_overlaps()));
+        assert_eq!(format!("{}", expected), format!("{}", rest));
+
+        let empty = make_crossword(vec![make_hello()]).without(0, words);
+        assert_eq!(Crossword::new(1), empty);
+        assert_eq!(format!("{}", Crossword::new(1)), format!("{}", empty));
+    }
+
     //   0 1 2 3 4
     // 0
     // 1
@@ -254,6 +416,29 @@ pub mod tests {
         assert!(!touching_crossword.is_valid());
     }
 
+    #[test]
+    fn from_positions() {
+        let (hello, hello_pos) = make_hello();
+        let (nag, nag_pos) = make_nag();
+        assert_eq!(Ok(make_hello_world()), Crossword::validate(&["hello", "world"], &[hello_pos, make_world().1]));
+        assert!(Crossword::from_positions(&[hello, nag], &[hello_pos, nag_pos]).is_ok());
+        assert_eq!(Err(CrosswordError::Collision { index: 1, word: "nag".to_string(), pos: nag_pos }),
+            Crossword::validate(&[hello, nag], &[hello_pos, nag_pos]));
+        let far_pos = Position { row: 8, col: 8, dir: Vertical };
+        assert_eq!(Err(CrosswordError::Disconnected { index: 1, word: "nag".to_string(), pos: far_pos }),
+            Crossword::validate(&[hello, nag], &[hello_pos, far_pos]));
+
+        let words: [&str; 0] = [];
+        assert_eq!(Err(CrosswordError::EmptyWordList), Crossword::from_positions(&words, &[]));
+        assert_eq!(Err(CrosswordError::WordTooShort { index: 1, word: "a".to_string() }),
+            Crossword::from_positions(&[hello, "a"], &[hello_pos, nag_pos]));
+        assert_eq!(Err(CrosswordError::PositionCount { num_words: 2, num_positions: 1 }),
+            Crossword::from_positions(&[hello, nag], &[hello_pos]));
+        let far = Position { row: 0, col: 120, dir: Horizontal };
+        assert_eq!(Err(CrosswordError::OutOfBounds { index: 0, word: "hello".to_string(), pos: far }),
+            Crossword::from_positions(&[hello], &[far]));
+    }
+
     #[test]
     fn is_valid_diagonal() {
         //   0 1 2 3 4
@@ -266,4 +451,15 @@ pub mod tests {
         assert!(diagonal_crossword.is_valid());
     }
 
+    #[test]
+    fn can_add_word_past_right_edge() {
+        //   0 1 2 3
+        // 0 t o o k
+        // 1 o
+        // 2 o
+        // 3 n
+        let crossword = make_crossword(vec![("toon", Position { row: 0, col: 0, dir: Vertical })]);
+        assert!(crossword.can_add_word("took", 4, Position { row: 0, col: 0, dir: Horizontal }));
+    }
+
 }
@@ -0,0 +1,168 @@
+//! The errors the library returns.
+
+use placement::{Position, MAX_COORD, MAX_WORD_LEN};
+use word_list::{WordListError, MIN_WORD_LEN};
+use config::ConfigError;
+
+/// Why a word list, layout or setting was rejected. Word indexes are 0-based.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CrosswordError {
+    /// There are no words to lay out.
+    EmptyWordList,
+    /// A word has fewer than `MIN_WORD_LEN` letters.
+    WordTooShort {
+        /// The word's index in the word list.
+        index: usize,
+        /// The word.
+        word: String
+    },
+    /// A word has more than `MAX_WORD_LEN` letters.
+    WordTooLong {
+        /// The word's index in the word list.
+        index: usize,
+        /// The word.
+        word: String
+    },
+    /// A layout has a different number of positions than words.
+    PositionCount {
+        /// The number of words.
+        num_words: usize,
+        /// The number of positions.
+        num_positions: usize
+    },
+    /// A position isn't `row,col,H` or `row,col,V`.
+    InvalidPosition {
+        /// The text that was parsed.
+        input: String,
+        /// What's wrong with it.
+        reason: String
+    },
+    /// A word at its position would go past row or col ±`MAX_COORD`.
+    OutOfBounds {
+        /// The word's index in the word list.
+        index: usize,
+        /// The word.
+        word: String,
+        /// Where it was placed.
+        pos: Position
+    },
+    /// A word collides with or touches a word before it where they don't cross.
+    Collision {
+        /// The word's index in the word list.
+        index: usize,
+        /// The word.
+        word: String,
+        /// Where it was placed.
+        pos: Position
+    },
+    /// A word isn't joined to the first word through crossings.
+    Disconnected {
+        /// The word's index in the word list.
+        index: usize,
+        /// The word.
+        word: String,
+        /// Where it was placed.
+        pos: Position
+    },
+    /// A word to lock isn't in the word list, or is already locked.
+    UnknownLockedWord {
+        /// The word.
+        word: String
+    },
+    /// A layout to optimize doesn't place a locked word where it's locked.
+    LockedWordMissing {
+        /// The word's index in the word list.
+        index: usize,
+        /// The word.
+        word: String,
+        /// Where it's locked.
+        pos: Position
+    },
+    /// A file couldn't be read or written.
+    Io {
+        /// The file's path.
+        path: String,
+        /// The IO error.
+        reason: String
+    },
+    /// A checkpoint file is malformed.
+    Checkpoint {
+        /// The path it was read from.
+        source: String,
+        /// The line number, from 1.
+        line: usize,
+        /// What's wrong with the line.
+        reason: String
+    },
+    /// A checkpoint was saved from a search with other words or settings.
+    CheckpointMismatch {
+        /// What differs.
+        reason: String
+    },
+    /// The search can't be saved as a checkpoint, with its settings.
+    CheckpointUnsupported {
+        /// Which setting stops it.
+        reason: String
+    },
+    /// A word list file couldn't be read.
+    WordList(WordListError),
+    /// A config file couldn't be read, or doesn't have the profile asked for.
+    Config(ConfigError)
+}
+use std::fmt::{Display, Formatter, Result};
+impl Display for CrosswordError {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match *self {
+            CrosswordError::EmptyWordList => write!(f, "the word list is empty"),
+            CrosswordError::WordTooShort { index, ref word } => {
+                write!(f, "word {} '{}' is too short, words need at least {} letters", index + 1, word, MIN_WORD_LEN)
+            },
+            CrosswordError::WordTooLong { index, ref word } => {
+                write!(f, "word {} '{}' is too long, words can have at most {} letters", index + 1, word, MAX_WORD_LEN)
+            },
+            CrosswordError::PositionCount { num_words, num_positions } => {
+                write!(f, "got {} placements for {} words", num_positions, num_words)
+            },
+            CrosswordError::InvalidPosition { ref input, ref reason } => {
+                write!(f, "invalid placement '{}': {}", input, reason)
+            },
+            CrosswordError::OutOfBounds { index, ref word, pos } => {
+                write!(f, "word {} '{}' at {} goes past row/col ±{}", index + 1, word, pos, MAX_COORD)
+            },
+            CrosswordError::Collision { index, ref word, pos } => {
+                write!(f, "word {} '{}' at {} collides with or touches an earlier word", index + 1, word, pos)
+            },
+            CrosswordError::Disconnected { index, ref word, pos } => {
+                write!(f, "word {} '{}' at {} doesn't cross the first word or any word that does", index + 1, word, pos)
+            },
+            CrosswordError::UnknownLockedWord { ref word } => {
+                write!(f, "can't lock '{}': it isn't in the word list, or is already locked", word)
+            },
+            CrosswordError::LockedWordMissing { index, ref word, pos } => {
+                write!(f, "word {} '{}' is locked at {}, but the layout doesn't place it there", index + 1, word, pos)
+            },
+            CrosswordError::Io { ref path, ref reason } => write!(f, "{}: {}", path, reason),
+            CrosswordError::Checkpoint { ref source, line, ref reason } => write!(f, "{}:{}: {}", source, line, reason),
+            CrosswordError::CheckpointMismatch { ref reason } => {
+                write!(f, "can't resume from the checkpoint: {}", reason)
+            },
+            CrosswordError::CheckpointUnsupported { ref reason } => {
+                write!(f, "can't save a checkpoint of the search: {}", reason)
+            },
+            CrosswordError::WordList(ref e) => write!(f, "{}", e),
+            CrosswordError::Config(ref e) => write!(f, "{}", e)
+        }
+    }
+}
+impl ::std::error::Error for CrosswordError {}
+
+impl From<WordListError> for CrosswordError {
+    fn from(e: WordListError) -> CrosswordError {
+        CrosswordError::WordList(e)
+    }
+}
+impl From<ConfigError> for CrosswordError {
+    fn from(e: ConfigError) -> CrosswordError {
+        CrosswordError::Config(e)
+    }
+}
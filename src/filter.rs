@@ -1,35 +1,47 @@
-use std::i16::MAX;
-use std::collections::HashSet;
 use std::collections::BTreeSet;
-use std::cell::Cell;
-use std::cell::RefCell;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicIsize, AtomicU64, Ordering};
 
 use placement::Position;
 use bounding_box::BoundingBox;
-use word_placements::WordPlacements;
+use word_placements::{WordPlacements, Dedup};
 use crossword::Crossword;
+use size::SizeLimit;
+use config::Config;
+use score::Score;
+use seen::{Seen, SeenStats};
+
+#[derive(Debug)]
+struct AreaBuckets {
+    overlap_areas: Vec<BTreeSet<i16>>,
+    max_area: (i8, i16)
+}
 #[derive(Debug)]
 struct OverlapAreas {
-    overlap_areas: RefCell<Vec<RefCell<BTreeSet<i16>>>>,
-    max_area: Cell<(i8, i16)>,
+    buckets: Mutex<AreaBuckets>,
+    // a copy of buckets.max_area.1, so by_area can read it without locking
+    max_area: AtomicIsize,
     pub num_areas: usize
 }
 impl OverlapAreas {
     fn new(num_areas: usize) -> OverlapAreas {
         OverlapAreas {
-            overlap_areas: RefCell::new(vec![]),
-            max_area: Cell::new((0, MAX)),
-            num_areas: num_areas
+            buckets: Mutex::new(AreaBuckets {
+                overlap_areas: vec![],
+                max_area: (0, i16::MAX)
+            }),
+            max_area: AtomicIsize::new(i16::MAX as isize),
+            num_areas
         }
     }
     fn get_max_area(&self) -> i16 {
-        self.max_area.get().1
+        self.max_area.load(Ordering::Relaxed) as i16
     }
-    fn reset_max_area(&self, overlaps: i8, area: i16) {
-        let max_area = self.overlap_areas.borrow().iter().enumerate().fold(
+    fn reset_max_area(&self, buckets: &mut AreaBuckets, overlaps: i8, area: i16) {
+        let max_area = buckets.overlap_areas.iter().enumerate().fold(
             (overlaps, area),
-            |(prev_overlaps, prev_area), (overlaps, cell_areas)| {
-                if let Some(area) = cell_areas.borrow().last() {
+            |(prev_overlaps, prev_area), (overlaps, areas)| {
+                if let Some(&area) = areas.iter().next_back() {
                     if area > prev_area {
                         return (overlaps as i8, area)
                     }
@@ -37,23 +49,19 @@ impl OverlapAreas {
                 (prev_overlaps, prev_area)
             }
         );
-        self.max_area.set(max_area);
-    }
-    fn extend_if_needed(&self, overlaps: i8) {
-        let overlaps = overlaps as usize;
-        let overlap_areas_len = self.overlap_areas.borrow().len();
-        if overlaps >= overlap_areas_len {
-            let mut overlap_areas = self.overlap_areas.borrow_mut();
-            overlap_areas.extend((overlap_areas_len..overlaps + 1).map(|_| RefCell::new(BTreeSet::new())));
-        }
+        buckets.max_area = max_area;
+        self.max_area.store(max_area.1 as isize, Ordering::Relaxed);
     }
     fn filter_by_area(&self, overlaps: i8, area: i16) -> bool {
-        let mut should_reset = false;
-        {
-            self.extend_if_needed(overlaps);
-            let overlap_areas =  self.overlap_areas.borrow();
-            let mut areas = overlap_areas[overlaps as usize].borrow_mut();
-            if let Some(last_area) = areas.last() {
+        let mut buckets = self.buckets.lock().unwrap();
+        let overlaps_index = overlaps as usize;
+        if overlaps_index >= buckets.overlap_areas.len() {
+            buckets.overlap_areas.resize(overlaps_index + 1, BTreeSet::new());
+        }
+        let should_reset = {
+            let max_area = buckets.max_area;
+            let areas = &mut buckets.overlap_areas[overlaps_index];
+            if let Some(&last_area) = areas.iter().next_back() {
                 if area > last_area {
                     return false;
                 }
@@ -62,61 +70,180 @@ impl OverlapAreas {
                         areas.remove(&last_area);
                     }
                     areas.insert(area);
-                    let (max_area_overlaps, max_area) = self.max_area.get();
-                    should_reset = overlaps == max_area_overlaps && area < max_area;
+                    let (max_area_overlaps, max_area) = max_area;
+                    overlaps == max_area_overlaps && area < max_area
+                } else {
+                    false
                 }
             } else {
                 areas.insert(area);
-                should_reset = true;
+                true
             }
-        }
+        };
         if should_reset {
-            self.reset_max_area(overlaps, area);
+            self.reset_max_area(&mut buckets, overlaps, area);
         }
         true
     }
 }
 
-#[derive(Debug)]
-pub struct Filter {
-    seen: RefCell<HashSet<WordPlacements>>,
-    overlap_areas: OverlapAreas,
-    has_min_areas: bool
+// the k lowest costs of finished layouts so far, by a Score. num_areas without
+// a score is the same idea, but per overlap count and by area.
+struct TopScores {
+    score: Arc<dyn Score>,
+    k: usize,
+    // distinct, lowest first
+    costs: Mutex<Vec<f64>>,
+    // the highest of the k costs once there are k, so by_area can read it without locking
+    max_cost: AtomicU64
 }
-trait CollectionWithLast<T> {
-    fn last(&self) -> Option<T>;
+impl TopScores {
+    fn new(score: Arc<dyn Score>, k: usize) -> TopScores {
+        TopScores {
+            score,
+            k,
+            costs: Mutex::new(vec![]),
+            max_cost: AtomicU64::new(f64::INFINITY.to_bits())
+        }
+    }
+    fn get_max_cost(&self) -> f64 {
+        f64::from_bits(self.max_cost.load(Ordering::Relaxed))
+    }
+    fn set_costs(&self, costs: &mut Vec<f64>, next_costs: Vec<f64>) {
+        *costs = next_costs;
+        let max_cost = if costs.len() == self.k { costs[self.k - 1] } else { f64::INFINITY };
+        self.max_cost.store(max_cost.to_bits(), Ordering::Relaxed);
+    }
+    fn filter_by_cost(&self, cost: f64) -> bool {
+        if self.k == 0 {
+            return true
+        }
+        let mut costs = self.costs.lock().unwrap();
+        if costs.len() == self.k && cost > costs[self.k - 1] {
+            return false
+        }
+        if !costs.contains(&cost) {
+            let mut next_costs = costs.clone();
+            let i = next_costs.iter().position(|&other| other > cost).unwrap_or(next_costs.len());
+            next_costs.insert(i, cost);
+            next_costs.truncate(self.k);
+            self.set_costs(&mut costs, next_costs);
+        }
+        true
+    }
 }
-impl<T: Copy> CollectionWithLast<T> for BTreeSet<T> {
-    fn last(&self) -> Option<T> {
-        self.iter().next_back().map(|&x| x)
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+impl Debug for TopScores {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "TopScores {{ score: {}, k: {}, costs: {:?} }}", self.score.name(), self.k, self.costs)
     }
 }
+
+// what a filter has learned so far, so that a search can be saved and resumed
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterState {
+    pub seen: Vec<WordPlacements>,
+    pub overlap_areas: Vec<Vec<i16>>,
+    pub max_area: (i8, i16),
+    pub costs: Vec<f64>
+}
+
+// shared by every search of a generator, so it can be used from several threads
+#[derive(Debug)]
+pub struct Filter {
+    seen: Seen,
+    overlap_areas: OverlapAreas,
+    has_min_areas: bool,
+    dedup: Dedup,
+    size: SizeLimit,
+    // with a score, num_areas keeps the top k by it instead of by area
+    top_scores: Option<TopScores>
+}
 impl Filter {
-    pub fn new(num_areas: usize) -> Filter {
+    pub fn new(config: &Config, score: Option<Arc<dyn Score>>) -> Filter {
         Filter {
-            seen: RefCell::new(HashSet::new()),
-            overlap_areas: OverlapAreas::new(num_areas),
-            has_min_areas: num_areas > 0
+            seen: Seen::new(config.seen, config.max_memory),
+            overlap_areas: OverlapAreas::new(config.num_areas),
+            has_min_areas: config.num_areas > 0 && score.is_none(),
+            dedup: config.get_dedup(),
+            size: config.size,
+            top_scores: score.map(|score| TopScores::new(score, config.num_areas))
         }
     }
-    pub fn num_areas(&self) -> usize {
-        self.overlap_areas.num_areas
+
+    pub fn score(&self, crossword: &Crossword) -> Option<f64> {
+        self.top_scores.as_ref().map(|top_scores| top_scores.score.cost(crossword))
+    }
+    pub fn has_score(&self) -> bool {
+        self.top_scores.is_some()
+    }
+    pub fn score_name(&self) -> Option<String> {
+        self.top_scores.as_ref().map(|top_scores| top_scores.score.name())
+    }
+
+    pub fn seen_stats(&self) -> SeenStats {
+        self.seen.stats()
+    }
+
+    pub fn save(&self) -> FilterState {
+        let buckets = self.overlap_areas.buckets.lock().unwrap();
+        FilterState {
+            seen: self.seen.save(),
+            overlap_areas: buckets.overlap_areas.iter().map(|areas| areas.iter().cloned().collect()).collect(),
+            max_area: buckets.max_area,
+            costs: self.top_scores.as_ref().map_or(vec![], |top_scores| top_scores.costs.lock().unwrap().clone())
+        }
+    }
+    // replaces what the filter has learned
+    pub fn restore(&self, state: FilterState) {
+        self.seen.restore(state.seen);
+        let mut buckets = self.overlap_areas.buckets.lock().unwrap();
+        buckets.overlap_areas = state.overlap_areas.into_iter().map(|areas| areas.into_iter().collect()).collect();
+        buckets.max_area = state.max_area;
+        self.overlap_areas.max_area.store(state.max_area.1 as isize, Ordering::Relaxed);
+        if let Some(ref top_scores) = self.top_scores {
+            top_scores.set_costs(&mut top_scores.costs.lock().unwrap(), state.costs);
+        }
     }
 
     pub fn by_area(&self, word_len: usize, next_pos: Position, bb: BoundingBox) -> bool {
-        if !self.has_min_areas {
+        if !self.has_min_areas && !self.size.is_limited() && self.top_scores.is_none() {
             return true
         }
-        let area = bb.combine_word_pos(word_len, next_pos).area();
-        area <= self.overlap_areas.get_max_area()
+        let bb = bb.combine_word_pos(word_len, next_pos);
+        if !self.size.fits(bb) {
+            return false
+        }
+        if let Some(ref top_scores) = self.top_scores {
+            if top_scores.score.lower_bound(bb).is_some_and(|bound| bound > top_scores.get_max_cost()) {
+                return false
+            }
+        }
+        !self.has_min_areas || bb.area() <= self.overlap_areas.get_max_area()
+    }
+    // whether a finished layout fills the frame, if it has to
+    pub fn by_size(&self, crossword: &Crossword) -> bool {
+        self.size.fills(crossword.bounding_box())
+    }
+    pub fn by_frame(&self, bb: BoundingBox, is_finished: bool) -> bool {
+        self.size.fits(bb) && (!is_finished || self.size.fills(bb))
     }
     pub fn by_seen(&self, crossword: &Crossword, num_remaining_words: usize) -> bool {
-        {
-            let mut seen = &mut self.seen.borrow_mut();
-            if seen.contains(&crossword.positions) {
-                return false
+        let positions = match self.dedup {
+            // a transposed partial layout may not fit the frame when this one does
+            Dedup::Canonical if !self.size.is_symmetric() => crossword.positions.translated(),
+            dedup => dedup.key(&crossword.positions)
+        };
+        if !self.seen.insert(positions) {
+            return false
+        }
+        if num_remaining_words == 1 && !self.by_size(crossword) {
+            return false
+        }
+        if let Some(ref top_scores) = self.top_scores {
+            if num_remaining_words == 1 {
+                return top_scores.filter_by_cost(top_scores.score.cost(crossword))
             }
-            seen.insert(crossword.positions.clone());
         }
         if self.has_min_areas && num_remaining_words == 1 {
             let area = crossword.bounding_box().area();
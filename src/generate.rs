@@ -1,176 +1,567 @@
-use std::rc::Rc;
-use std::cell::{RefCell, Cell};
+//! The generator, which searches for layouts of its words.
+
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::cmp::max;
 
 use crossword::Crossword;
+use bounding_box::BoundingBox;
 use placement::{Position, START_POSITION};
 use filter::Filter;
-use rand::{hash, rand_range};
+use search::{Search, MultiSearch};
+use checkpoint::Checkpoint;
+use rand::{hash, rand_range, rand_range_inverse};
+use word_list::{Entry, DEFAULT_WEIGHT, check_words};
+use budget::{Budget, Tracker, StopReason, Stats, Rejection};
+use config::Config;
+use score::Score;
+use strategy::{Strategy, Beam, Lds};
+use optimize::{Optimizer, fill};
+use letter_index::{LetterIndex, GridLetters};
+use observe::{CancelToken, Observer};
+use error::CrosswordError;
 
-pub struct Generator<'a> {
-    seed: u64,
-    next_seed: Cell<u64>,
-    word_list: Vec<&'a String>,
+/// Searches for every layout that interlocks all of its words.
+///
+/// Owns its words and keeps its search state behind locks, so it is `Send + Sync`:
+/// it can be kept in a long-lived struct, or shared between threads in an `Arc`.
+pub struct Generator {
+    config: Config,
+    next_seed: Mutex<u64>,
+    word_list: Vec<Arc<str>>,
     word_chars_list: Vec<Vec<char>>,
+    letter_index: LetterIndex,
+    // the words to start searches from, unless words are locked. see anchors
+    anchor_order: Vec<usize>,
+    clues: Vec<Option<Arc<str>>>,
+    weights: Vec<u32>,
     filter: Filter,
+    tracker: Tracker,
+    // words placed before the search starts, in the order they were locked
+    locked: Vec<(usize, Position)>,
+    // the most weight placed by a partial solution so far
+    best_partial: AtomicU64,
+    observer: Option<Arc<dyn Observer>>,
+    // the lowest cost of a solution so far, for Observer::new_best
+    best_cost: Mutex<Option<f64>>
 }
-impl<'a> Generator<'a> {
-    pub fn new(words: Vec<&'a String>, num_areas: usize, seed: u64) -> Generator<'a> {
+impl Generator {
+    /// A generator with the default settings but for `num_areas` and `seed`, see
+    /// [`Config::new`](../config/struct.Config.html#method.new).
+    pub fn new<S: AsRef<str>>(words: &[S], num_areas: usize, seed: u64) -> ::std::result::Result<Generator, CrosswordError> {
+        Generator::with_config(words, Config::new(num_areas, seed))
+    }
+    /// Fails with a `CrosswordError` if there are no words, or a word is too short
+    /// or too long to place.
+    pub fn with_config<S: AsRef<str>>(words: &[S], config: Config) -> ::std::result::Result<Generator, CrosswordError> {
+        check_words(words)?;
+        let n = words.len();
+        let word_chars_list: Vec<Vec<char>> = words.iter().map(|word| word.as_ref().chars().collect()).collect();
+        let mut anchor_order = config.anchor.anchors(&word_chars_list);
+        if config.partial {
+            // the anchor word may be one that's best left out, so each word anchors a
+            // search in turn, the anchor setting's first
+            let rest: Vec<usize> = (0..n).filter(|i| !anchor_order.contains(i)).collect();
+            anchor_order.extend(rest);
+        }
+        Ok(Generator {
+            next_seed: Mutex::new(config.seed),
+            word_list: words.iter().map(|word| Arc::from(word.as_ref())).collect(),
+            letter_index: LetterIndex::new(&word_chars_list),
+            anchor_order,
+            word_chars_list,
+            clues: vec![None; n],
+            weights: vec![DEFAULT_WEIGHT; n],
+            filter: Filter::new(&config, config.score.clone().map(|score| Arc::new(score) as Arc<dyn Score>)),
+            tracker: Tracker::new(config.budget),
+            locked: vec![],
+            best_partial: AtomicU64::new(0),
+            observer: None,
+            best_cost: Mutex::new(None),
+            config
+        })
+    }
+    /// Like `with_config`, but also keeps each entry's clue and weight.
+    pub fn from_entries(entries: &[Entry], config: Config) -> ::std::result::Result<Generator, CrosswordError> {
+        let words: Vec<&str> = entries.iter().map(|entry| &entry.word[..]).collect();
+        Ok(Generator {
+            clues: entries.iter().map(|entry| entry.clue.as_ref().map(|clue| Arc::from(&clue[..]))).collect(),
+            weights: entries.iter().map(|entry| entry.weight).collect(),
+            ..Generator::with_config(&words, config)?
+        })
+    }
+
+    /// Limits the run. The clock starts when iteration starts.
+    pub fn with_budget(self, budget: Budget) -> Generator {
+        Generator {
+            config: Config { budget, ..self.config },
+            tracker: Tracker::new(budget).with_cancel_token(self.tracker.cancel_token()),
+            ..self
+        }
+    }
+    /// Stops the run when the token is cancelled, e.g. to share one token between
+    /// generators. Every generator has a token of its own otherwise, see
+    /// `cancel_token`.
+    pub fn with_cancel_token(self, cancel: CancelToken) -> Generator {
         Generator {
-            seed: seed,
-            next_seed: Cell::new(seed),
-            word_list: words.clone(),
-            word_chars_list: words.iter().map(|word| word.chars().collect()).collect(),
-            filter: Filter::new(num_areas)
+            tracker: Tracker::new(self.config.budget).with_cancel_token(cancel),
+            ..self
         }
     }
+    /// The token that stops this generator's run, to cancel it from another thread.
+    pub fn cancel_token(&self) -> CancelToken {
+        self.tracker.cancel_token()
+    }
+    /// Tells the observer about each placement, backtrack and solution, see `Observer`.
+    pub fn with_observer(self, observer: Arc<dyn Observer>) -> Generator {
+        Generator {
+            observer: Some(observer),
+            ..self
+        }
+    }
+    /// Pins words at their positions, and searches over the rest of the words
+    /// around them. A word listed twice can be locked twice.
+    pub fn with_locked<S: AsRef<str>>(self, locked: &[(S, Position)]) -> ::std::result::Result<Generator, CrosswordError> {
+        let mut locked_indexes: Vec<(usize, Position)> = vec![];
+        let mut crossword = Crossword::new(self.word_list.len());
+        for &(ref word, pos) in locked {
+            let word = word.as_ref();
+            let word_index = match (0..self.word_list.len()).find(|&i| self.word(i) == word && locked_indexes.iter().all(|&(j, _)| i != j)) {
+                Some(word_index) => word_index,
+                None => return Err(CrosswordError::UnknownLockedWord { word: word.to_string() })
+            };
+            let word_len = self.word_chars_list[word_index].len();
+            if !pos.fits(word_len) {
+                return Err(CrosswordError::OutOfBounds { index: word_index, word: word.to_string(), pos })
+            }
+            crossword = crossword.set(word, word_len, word_index, pos);
+            if !crossword.is_valid() {
+                return Err(CrosswordError::Collision { index: word_index, word: word.to_string(), pos })
+            }
+            locked_indexes.push((word_index, pos));
+        }
+        Ok(Generator {
+            locked: locked_indexes,
+            ..self
+        })
+    }
+    /// Ranks layouts by `score` instead of the score in the config, if any. See `Score`.
+    pub fn with_score(self, score: Arc<dyn Score>) -> Generator {
+        Generator {
+            filter: Filter::new(&self.config, Some(score)),
+            ..self
+        }
+    }
+    /// The cost of a layout by the generator's score, if it has one.
+    pub fn score(&self, crossword: &Crossword) -> Option<f64> {
+        self.filter.score(crossword)
+    }
+    /// The locked words' indexes and positions, in the order they were locked.
+    pub fn locked(&self) -> &[(usize, Position)] {
+        &self.locked
+    }
+    /// Whether the word is locked in place.
+    pub fn is_locked(&self, word_index: usize) -> bool {
+        self.locked.iter().any(|&(i, _)| i == word_index)
+    }
+
+    /// The settings the generator was made with.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+    /// Why iteration ended, or `Exhausted` if it ran to completion (or hasn't stopped yet).
+    pub fn stop_reason(&self) -> StopReason {
+        self.tracker.stop_reason()
+    }
+    /// How the run is going: safe to call from another thread while it runs.
+    pub fn stats(&self) -> Stats {
+        Stats { seen: self.filter.seen_stats(), ..self.tracker.stats() }
+    }
+    pub(crate) fn tracker(&self) -> &Tracker {
+        &self.tracker
+    }
+    pub(crate) fn filter(&self) -> &Filter {
+        &self.filter
+    }
+
+    /// The number of words, placed or not.
+    pub fn num_words(&self) -> usize {
+        self.word_list.len()
+    }
+    /// The word at an index of the word list.
+    pub fn word(&self, word_index: usize) -> &str {
+        &self.word_list[word_index]
+    }
+    /// The word's clue, if its entry had one.
+    pub fn clue(&self, word_index: usize) -> Option<&str> {
+        self.clues[word_index].as_ref().map(|clue| &clue[..])
+    }
+    /// The word's weight, 1 unless its entry said otherwise.
+    pub fn weight(&self, word_index: usize) -> u32 {
+        self.weights[word_index]
+    }
+    /// The placed words of a crossword with their positions and clues, in word list order.
+    pub fn clues_for<'b>(&'b self, crossword: &'b Crossword) -> impl Iterator<Item=(Position, &'b str, Option<&'b str>)> + 'b {
+        (0..self.word_list.len()).filter_map(move |i| {
+            crossword.positions.get(i).map(|pos| (pos, self.word(i), self.clue(i)))
+        })
+    }
 
-    fn get_seed(&self) -> u64 {
-        let seed = self.next_seed.get();
-        self.next_seed.set(hash(seed, self.seed));
+    pub(crate) fn get_seed(&self) -> u64 {
+        let mut next_seed = self.next_seed.lock().unwrap();
+        let seed = *next_seed;
+        *next_seed = hash(seed, self.config.seed);
         seed
     }
 
-    fn get_init(&self) -> (Crossword, Rc<Vec<usize>>) {
-        let first_word = self.word_list[0];
-        let first_word_len = self.word_chars_list[0].len();
-        let init_crossword = Crossword::new(self.word_list.len()).set(first_word, first_word_len, 0, START_POSITION);
-        let candidates = (1..self.word_list.len()).collect();
-        (init_crossword, Rc::new(candidates))
-    }
-
-    pub fn iter<'b>(&'b self) -> Box<Iterator<Item=Crossword> + 'b> {
-        let (init_crossword, candidates) = self.get_init();
-        self.from_word_vec_recursive(init_crossword, candidates, self.get_seed())
-    }
-
-    pub fn multi_iter<'b>(&'b self, num_iters: usize) -> Box<Iterator<Item=Crossword> + 'b> {
-        let (init_crossword, candidates) = self.get_init();
-        let seeds = vec![self.get_seed(); num_iters];
-        let mut iters = vec![];
-        for i in 0..num_iters {
-            let iter = self.from_word_vec_partial(init_crossword.clone(), candidates.clone(), seeds[i]);
-            iters.push(RefCell::new(iter));
-        }
-        Box::new((0..num_iters)
-            .cycle()
-            .scan(false, move |state, i| {
-                let mut iter = iters[i].borrow_mut();
-                let r = if let Some((crossword, candidates)) = iter.next() {
-                    *state = true;
-                    Some(Some(self.from_word_vec_recursive(crossword, candidates, seeds[i])))
-                } else {
-                    Some(None)
-                };
-                if i == num_iters - 1 {
-                    if *state == false {
-                        return None
-                    } else {
-                        *state = false
-                    }
-                }
-                r
+    pub(crate) fn peek_next_seed(&self) -> u64 {
+        *self.next_seed.lock().unwrap()
+    }
+    pub(crate) fn set_next_seed(&self, seed: u64) {
+        *self.next_seed.lock().unwrap() = seed;
+    }
+
+    /// The words to start searches from, as chosen by the anchor setting. In
+    /// partial mode every word follows those in turn, and a search doesn't place the
+    /// words of the searches before it. With locked words, there's one search, from
+    /// the first locked word.
+    pub fn anchors(&self) -> Vec<usize> {
+        let anchors = match self.locked.first() {
+            Some(&(word_index, _)) => vec![word_index],
+            None => self.anchor_order.clone()
+        };
+        anchors.into_iter()
+            .filter(|&anchor| {
+                // it's finished from the start if every word is placed
+                let (crossword, candidates) = self.get_init(anchor);
+                self.filter.by_frame(crossword.bounding_box(), candidates.is_empty())
             })
-            .filter_map(|x| x)
-            .flat_map(|x| x)
-        )
+            .collect()
+    }
+    // the anchor word at the start position, or else the locked words
+    pub(crate) fn get_init(&self, anchor: usize) -> (Crossword, Arc<Vec<usize>>) {
+        let placed = if self.locked.is_empty() { vec![(anchor, START_POSITION)] } else { self.locked.clone() };
+        let init_crossword = placed.iter().fold(Crossword::new(self.word_list.len()), |crossword, &(word_index, pos)| {
+            crossword.set(&self.word_list[word_index], self.word_chars_list[word_index].len(), word_index, pos)
+        });
+        let candidates = (0..self.word_list.len()).filter(|&i| placed.iter().all(|&(j, _)| i != j)).collect();
+        (init_crossword, Arc::new(candidates))
+    }
+    pub(crate) fn get_inits(&self) -> Vec<(Crossword, Arc<Vec<usize>>)> {
+        self.anchors().into_iter().map(|anchor| self.get_init(anchor)).collect()
     }
 
-    fn from_word_vec_partial<'b>(&'b self, crossword: Crossword, candidates: Rc<Vec<usize>>, seed: u64) -> Box<Iterator<Item=(Crossword, Rc<Vec<usize>>)> + 'b> {
-        let n = candidates.len();
-        if n <= 6 {
-            return Box::new(Some((crossword, candidates)).into_iter());
+    /// Searches by the config's strategy.
+    pub fn iter<'b>(&'b self) -> Box<dyn Iterator<Item=Crossword> + 'b> {
+        match self.config.strategy {
+            Strategy::Dfs => Box::new(self.search()),
+            Strategy::Beam { width } => Box::new(Beam::new(self, width)),
+            Strategy::Lds { discrepancies } => Box::new(Lds::new(self, discrepancies))
         }
-        Box::new(self.from_word_vec(crossword, candidates, seed)
-            .flat_map(move |(next_crossword, next_candidates)| {
-                self.from_word_vec_partial(next_crossword, next_candidates, seed)
-            }))
+    }
+    /// The depth-first search, whatever the strategy, which can be saved with
+    /// `Search::checkpoint`.
+    pub fn search(&self) -> Search<'_> {
+        Search::new(self)
+    }
+    /// Improves a layout of the generator's words, see `Optimizer`. Fails if the
+    /// layout has a different number of words, or doesn't place the locked words
+    /// where they're locked.
+    pub fn optimize(&self, start: Crossword) -> ::std::result::Result<Optimizer<'_>, CrosswordError> {
+        let num_positions = start.positions.as_slice().len();
+        if num_positions != self.num_words() {
+            return Err(CrosswordError::PositionCount { num_words: self.num_words(), num_positions })
+        }
+        if let Some(&(index, pos)) = self.locked.iter().find(|&&(i, pos)| start.positions.get(i) != Some(pos)) {
+            return Err(CrosswordError::LockedWordMissing { index, word: self.word(index).to_string(), pos })
+        }
+        Ok(Optimizer::new(self, start))
+    }
+    /// A layout to optimize: the anchor and locked words, then each other word where
+    /// it fits best, if it fits.
+    pub fn greedy(&self) -> Crossword {
+        let init = self.get_inits().into_iter().next().map_or(Crossword::new(self.num_words()), |(crossword, _)| crossword);
+        fill(self, init)
+    }
+    /// Continues a saved search. The generator needs the same words and settings as
+    /// the one that saved it, see `Search::resume`, but gets a fresh budget.
+    pub fn resume(&self, checkpoint: &Checkpoint) -> ::std::result::Result<Search<'_>, CrosswordError> {
+        Search::resume(self, checkpoint)
     }
 
-    fn from_word_vec_recursive<'b>(&'b self, crossword: Crossword, candidates: Rc<Vec<usize>>, seed: u64) -> Box<Iterator<Item=Crossword> + 'b> {
-        let n = candidates.len();
-        if n == 0 {
-            return Box::new(Some(crossword).into_iter());
-        }
-        Box::new(self.from_word_vec(crossword, candidates, seed)
-            .flat_map(move |(next_crossword, next_candidates)| {
-                self.from_word_vec_recursive(next_crossword, next_candidates, seed)
-            }))
-    }
-
-    fn from_word_vec<'b>(&'b self, crossword: Crossword, candidates: Rc<Vec<usize>>, seed: u64) -> Box<Iterator<Item=(Crossword, Rc<Vec<usize>>)> + 'b> {
-        let &Generator {
-            seed: _,
-            next_seed: _,
-            ref filter,
-            ref word_list,
-            ref word_chars_list
-        } = self;
-        let n = candidates.len();
+    /// Interleaves `num_iters` depth-first searches, each over its own share of the
+    /// layouts with at most six words left, taking a layout from each in turn.
+    pub fn multi_iter<'b>(&'b self, num_iters: usize) -> Box<dyn Iterator<Item=Crossword> + 'b> {
+        Box::new(MultiSearch::new(self, num_iters))
+    }
+
+    // what a layout yields when the search can't place another word on it: itself,
+    // if every word is placed, or with partial solutions on, maybe itself anyway
+    pub(crate) fn leaf(&self, crossword: Crossword, candidates: &[usize]) -> Option<Crossword> {
+        if candidates.is_empty() {
+            Some(crossword)
+        } else if self.config.partial {
+            self.partial_solution(crossword, candidates)
+        } else {
+            None
+        }
+    }
+    // a layout that none of the words left can be added to. it's yielded if it
+    // places at least as much weight as every solution before it, partial or not,
+    // so the best come last.
+    pub(crate) fn partial_solution(&self, crossword: Crossword, candidates: &[usize]) -> Option<Crossword> {
+        // the search was cut short, rather than being stuck
+        if self.tracker.is_stopped() || !self.filter.by_size(&crossword) {
+            return None
+        }
+        // the words left were only turned down as seen or not good enough, so the
+        // layouts with them are found elsewhere
+        if self.can_extend(&crossword, candidates) {
+            return None
+        }
+        // the anchor or locked words alone were never placed, so they haven't been
+        // checked against the seen set yet
+        let num_placed = crossword.positions.as_slice().iter().filter(|pos| pos.is_some()).count();
+        if num_placed == max(self.locked.len(), 1) && !self.filter.by_seen(&crossword, 0) {
+            return None
+        }
+        let weight = self.placed_weight(&crossword);
+        let best = self.best_partial.fetch_max(weight, Ordering::Relaxed);
+        if weight >= best { Some(crossword) } else { None }
+    }
+    // whether any of the words left can cross the layout without running into it,
+    // within the size limit
+    fn can_extend(&self, crossword: &Crossword, candidates: &[usize]) -> bool {
         let bb = crossword.bounding_box();
-        let letters = Rc::new(crossword.letters().clone());
-        let letters_len = letters.len();
-        let crossword = Rc::new(crossword);
-        let rc_candidates = candidates.clone();
-        let seed = if n < 6 { 0 } else { hash(&crossword.positions, seed) };
-        let get_words = || {
-            (0..n).map(rand_range(n, hash(seed, seed)))
-                .map(move |candidate_index| {
-                    let word_index = rc_candidates[candidate_index];
-                    let word_chars: &Vec<char> = &word_chars_list[word_index];
-                    let word_len = word_chars.len();
-                    (word_index, word_len, candidate_index)
+        candidates.iter().any(|&word_index| {
+            let word = &self.word_list[word_index];
+            let word_len = self.word_chars_list[word_index].len();
+            crossword.letters().iter().any(|&(c, pos)| {
+                self.letter_index.offsets(word_index, c).iter().any(|&(_, offset)| {
+                    let next_pos = pos.from_offset(offset as i8);
+                    next_pos.fits(word_len)
+                        && self.config.size.fits(bb.combine_word_pos(word_len, next_pos))
+                        && crossword.can_add_word(word, word_len, next_pos)
                 })
-        };
-        let get_letters = move |w| {
-            let letters = letters.clone();
-            (0..letters_len).map(rand_range(letters_len, hash(w, seed)))
-                .map(move |i| (w, letters[i]))
-        };
-        let get_word_chars = move |((word_index, word_len, candidate_index), char_pos)| {
-            (0..word_len).map(rand_range(word_len, hash(char_pos, seed)))
-                .map(move |i2| ((word_index, word_len, candidate_index), char_pos, i2))
-        };
-        let filter_candidates = move |((word_index, word_len, candidate_index), (c1, pos), i2)| {
-            let word_chars: &Vec<char> = &word_chars_list[word_index];
-            let c2 = word_chars[i2];
-            if c1 != c2 {
-                return None
-            }
-            let word: &String = word_list[word_index];
-            let pos: Position = pos;
-            let next_pos = pos.from_offset(i2 as i8);
-            if !filter.by_area(word_len, next_pos, bb) {
-                return None
-            }
-            if !crossword.can_add_word(word, word_len, next_pos) {
-                return None
+            })
+        })
+    }
+    // whether a word anchored a partial search before the one the layout is from,
+    // which has already searched every layout with it
+    fn is_earlier_anchor(&self, crossword: &Crossword, word_index: usize) -> bool {
+        if !self.config.partial || !self.locked.is_empty() {
+            return false
+        }
+        let rank = |i: usize| self.anchor_order.iter().position(|&j| j == i);
+        let anchor = (0..self.word_list.len()).find(|&i| crossword.positions.get(i) == Some(START_POSITION));
+        anchor.is_some_and(|anchor| rank(word_index) < rank(anchor))
+    }
+    // counts a solution, and tells the observer
+    pub(crate) fn found(&self, crossword: &Crossword) {
+        self.tracker.add_result();
+        // partial solutions placing less weight aren't worth yielding after this
+        self.best_partial.fetch_max(self.placed_weight(crossword), Ordering::Relaxed);
+        if let Some(ref observer) = self.observer {
+            observer.solution(crossword);
+            let cost = self.score(crossword).unwrap_or(crossword.bounding_box().area() as f64);
+            let mut best_cost = self.best_cost.lock().unwrap();
+            if best_cost.is_none_or(|best_cost| cost < best_cost) {
+                *best_cost = Some(cost);
+                observer.new_best(crossword, cost);
             }
-            let next_crossword = crossword.set(word, word_len, word_index, next_pos);
-            if !filter.by_seen(&next_crossword, n) {
+        }
+    }
+    pub(crate) fn backtracked(&self, crossword: &Crossword) {
+        if let Some(ref observer) = self.observer {
+            observer.backtracked(crossword);
+        }
+    }
+    pub(crate) fn best_partial(&self) -> u64 {
+        self.best_partial.load(Ordering::Relaxed)
+    }
+    pub(crate) fn set_best_partial(&self, weight: u64) {
+        self.best_partial.store(weight, Ordering::Relaxed);
+    }
+    /// The sum of the weights of the words a crossword places.
+    pub fn placed_weight(&self, crossword: &Crossword) -> u64 {
+        (0..self.word_list.len())
+            .filter(|&i| crossword.positions.get(i).is_some())
+            .map(|i| self.weights[i] as u64)
+            .sum()
+    }
+    /// The words a crossword leaves out, in word list order.
+    pub fn left_out<'b>(&'b self, crossword: &'b Crossword) -> impl Iterator<Item=&'b str> + 'b {
+        (0..self.word_list.len())
+            .filter(move |&i| crossword.positions.get(i).is_none())
+            .map(move |i| self.word(i))
+    }
+
+    // the layouts that place one more of the candidates on a layout, in the seed's order
+    pub(crate) fn children<'b>(&'b self, crossword: Crossword, candidates: Arc<Vec<usize>>, seed: u64) -> Box<dyn Iterator<Item=(Crossword, Arc<Vec<usize>>)> + 'b> {
+        Box::new(
+            self.uncommitted_children(crossword, candidates.clone(), seed)
+            .filter_map(move |(next_crossword, w)| self.commit(next_crossword, &candidates, w))
+        )
+    }
+    // the same layouts as children, with the word each places, before the seen set
+    // or the observer hear of them. a search that only keeps some of them commits
+    // those it keeps.
+    pub(crate) fn uncommitted_children<'b>(&'b self, crossword: Crossword, candidates: Arc<Vec<usize>>, seed: u64) -> Box<dyn Iterator<Item=(Crossword, Candidate)> + 'b> {
+        let n = candidates.len();
+        let bb = crossword.bounding_box();
+        let grid = GridLetters::new(crossword.letters());
+        let crossword = Arc::new(crossword);
+        let seed = frame_seed(&crossword, n, seed);
+        // only the words that can cross, and the placements of each where its letter
+        // matches, in the same order as trying every one of them
+        let get_crossings = move |candidate_index| {
+            let w = self.candidate(&candidates, candidate_index);
+            let crossings = self.crossings(w, crossword.letters(), &grid, seed);
+            let crossword = crossword.clone();
+            crossings.into_iter().map(move |(_, letter, i2)| (crossword.clone(), w, crossword.letters()[letter], i2))
+        };
+        let filter_candidates = move |(crossword, w, letter, i2): (Arc<Crossword>, Candidate, (char, Position), usize)| {
+            if !self.tracker.check() {
                 return None
             }
-            let mut next_candidates = (*candidates).clone();
-            next_candidates.remove(candidate_index);
-            let next_candidates = Rc::new(next_candidates);
-            Some((next_crossword, next_candidates))
+            self.fit(&crossword, bb, w, letter, i2).map(|next_crossword| (next_crossword, w))
         };
         Box::new(
-            get_words()
-            .flat_map(get_letters)
-            .flat_map(get_word_chars)
+            (0..n).map(word_order(n, seed))
+            .flat_map(get_crossings)
             .filter_map(filter_candidates)
         )
     }
+    // each placement of word w over one of the layout's letters with the same letter:
+    // its letter_rank and char_rank, the index of the layout's letter and the letter
+    // of the word, in the order the ranks give
+    pub(crate) fn crossings(&self, w: Candidate, letters: &[(char, Position)], grid: &GridLetters, seed: u64) -> Vec<Crossing> {
+        if !self.letter_index.crosses(w.0, grid) {
+            return vec![]
+        }
+        let letter_rank = letter_rank(w, letters.len(), seed);
+        let mut crossings: Vec<Crossing> = self.letter_index.crossings(w.0, grid)
+            .map(|(letter, i2)| ((letter_rank(letter), char_rank(w, letters[letter], seed)(i2)), letter, i2))
+            .collect();
+        crossings.sort_unstable();
+        crossings
+    }
+    pub(crate) fn word_chars(&self, word_index: usize) -> &[char] {
+        &self.word_chars_list[word_index]
+    }
+    pub(crate) fn letter_index(&self) -> &LetterIndex {
+        &self.letter_index
+    }
+
+    pub(crate) fn candidate(&self, candidates: &[usize], candidate_index: usize) -> Candidate {
+        let word_index = candidates[candidate_index];
+        (word_index, self.word_chars_list[word_index].len(), candidate_index)
+    }
+
+    // places letter i2 of word w over the same letter of the crossword, if that
+    // makes a new layout that passes the filters
+    pub(crate) fn place(&self, crossword: &Crossword, bb: BoundingBox, candidates: &Arc<Vec<usize>>, w: Candidate, letter: (char, Position), i2: usize) -> Option<(Crossword, Arc<Vec<usize>>)> {
+        let next_crossword = self.fit(crossword, bb, w, letter, i2)?;
+        self.commit(next_crossword, candidates, w)
+    }
+    // the layout with letter i2 of word w over the same letter of the crossword, if
+    // it fits there. it isn't checked against the seen set yet.
+    fn fit(&self, crossword: &Crossword, bb: BoundingBox, w: Candidate, letter: (char, Position), i2: usize) -> Option<Crossword> {
+        let (word_index, word_len, _) = w;
+        let (c1, pos) = letter;
+        debug_assert_eq!(c1, self.word_chars_list[word_index][i2]);
+        let word: &Arc<str> = &self.word_list[word_index];
+        let next_pos = pos.from_offset(i2 as i8);
+        if !next_pos.fits(word_len) || self.is_earlier_anchor(crossword, word_index) {
+            return None
+        }
+        if !self.filter.by_area(word_len, next_pos, bb) {
+            self.tracker.reject(Rejection::Area);
+            return None
+        }
+        if !crossword.can_add_word(word, word_len, next_pos) {
+            self.tracker.reject(Rejection::Collision);
+            return None
+        }
+        Some(crossword.set(word, word_len, word_index, next_pos))
+    }
+    // adds a layout that placed word w to the seen set, counts it and tells the
+    // observer, unless it's been seen or filtered out
+    pub(crate) fn commit(&self, next_crossword: Crossword, candidates: &Arc<Vec<usize>>, w: Candidate) -> Option<(Crossword, Arc<Vec<usize>>)> {
+        let (word_index, _, candidate_index) = w;
+        if !self.filter.by_seen(&next_crossword, candidates.len()) {
+            self.tracker.reject(Rejection::Seen);
+            return None
+        }
+        self.tracker.add_node();
+        // a complete layout raises the bar for partial solutions now, not once it's
+        // yielded, which ParIter does on another thread
+        if candidates.len() == 1 && self.config.partial {
+            self.best_partial.fetch_max(self.placed_weight(&next_crossword), Ordering::Relaxed);
+        }
+        if let Some(ref observer) = self.observer {
+            observer.placed(&next_crossword, word_index);
+        }
+        let mut next_candidates = (**candidates).clone();
+        next_candidates.remove(candidate_index);
+        Some((next_crossword, Arc::new(next_candidates)))
+    }
+}
+
+// word_index, word_len and index into the candidates of a word to place
+pub(crate) type Candidate = (usize, usize, usize);
+// a placement's letter_rank and char_rank, index into the layout's letters and
+// letter of the word, see Generator::crossings
+pub(crate) type Crossing = ((usize, usize), usize, usize);
+
+// the order placements are tried in depends only on the seed and the layout so far,
+// so a search can pick up part way through a layout's placements (see Search)
+pub(crate) fn frame_seed(crossword: &Crossword, n: usize, seed: u64) -> u64 {
+    if n < 6 { 0 } else { hash(&crossword.positions, seed) }
 }
+// maps 0..n to the candidate indexes of the words to place
+pub(crate) fn word_order(n: usize, seed: u64) -> impl Fn(usize) -> usize {
+    rand_range(n, hash(seed, seed))
+}
+// where each of the crossword's letters comes in the order word w tries to cross
+// them, a shuffle of 0..letters_len
+fn letter_rank(w: Candidate, letters_len: usize, seed: u64) -> impl Fn(usize) -> usize {
+    rand_range_inverse(letters_len, hash(w, seed))
+}
+// where each letter of word w comes in the order it's tried over the crossword's
+// letter, a shuffle of 0..word_len
+fn char_rank(w: Candidate, letter: (char, Position), seed: u64) -> impl Fn(usize) -> usize {
+    rand_range_inverse(w.1, hash(letter, seed))
+}
+
 use std::fmt::{Display, Formatter, Result};
-impl<'a> Display for Generator<'a> {
+impl Display for Generator {
     fn fmt(&self, f: &mut Formatter) -> Result {
         writeln!(f, "word_list:")?;
         for word in &self.word_list {
             writeln!(f, "  - {}", word)?;
         }
-        writeln!(f, "num_areas: {}", self.filter.num_areas())?;
-        writeln!(f, "seed: {}", self.seed)
+        if self.clues.iter().any(|clue| clue.is_some()) {
+            writeln!(f, "clues:")?;
+            for (word, clue) in self.word_list.iter().zip(&self.clues) {
+                if let Some(ref clue) = *clue {
+                    writeln!(f, "  {}: {:?}", word, clue)?;
+                }
+            }
+        }
+        if self.weights.iter().any(|&weight| weight != DEFAULT_WEIGHT) {
+            writeln!(f, "weights:")?;
+            for (word, weight) in self.word_list.iter().zip(&self.weights) {
+                writeln!(f, "  {}: {}", word, weight)?;
+            }
+        }
+        if !self.locked.is_empty() {
+            writeln!(f, "locked:")?;
+            for &(word_index, pos) in &self.locked {
+                writeln!(f, "  - {{ position: [{}], word: {} }}", pos, self.word(word_index))?;
+            }
+        }
+        write!(f, "{}", self.config)
     }
 }
 
@@ -179,18 +570,38 @@ pub mod tests {
     use super::*;
 
     use placement::{ Position };
+    use anchor::Anchor;
     use placement::Direction::{ Horizontal, Vertical };
     use crossword::tests::make_crossword;
 
-    type WordPosition = (&'static str, Position);
-
-    pub fn test_generator(words: Vec<&str>, num_areas: usize, test_fn: &Fn(Generator) -> ()) {
-        let words = words.iter().map(|s| s.to_string()).collect::<Vec<_>>();
-        let words = words.iter().map(|s| s).collect();
-        let gen = Generator::new(words, num_areas, 0);
+    pub fn test_generator(words: Vec<&str>, num_areas: usize, test_fn: &dyn Fn(Generator)) {
+        let gen = Generator::new(&words, num_areas, 0).unwrap();
         test_fn(gen);
     }
 
+    #[test]
+    fn send_sync() {
+        fn assert_send_sync<T: Send + Sync + 'static>() {}
+        assert_send_sync::<Generator>();
+    }
+
+    #[test]
+    fn owned() {
+        use std::thread;
+        // the generator outlives the words it was made from
+        let gen = {
+            let words = vec!["ton".to_string(), "tok".to_string(), "nob".to_string(), "kob".to_string()];
+            Generator::new(&words, 0, 0).unwrap()
+        };
+        let gen = Arc::new(gen);
+        let count = {
+            let gen = gen.clone();
+            thread::spawn(move || gen.iter().count()).join().unwrap()
+        };
+        assert_eq!(1, count);
+        assert_eq!("tok", gen.word(1));
+    }
+
 
     #[test]
     fn display() {
@@ -204,6 +615,19 @@ pub mod tests {
         });
     }
 
+    #[test]
+    fn display_entries() {
+        let entries = vec![
+            Entry { word: "hello".to_string(), clue: Some("a \"greeting\"".to_string()), weight: 2 },
+            Entry::new("world")
+        ];
+        let gen = Generator::from_entries(&entries, Config::default()).unwrap();
+        let expected = "word_list:\n  - hello\n  - world\nclues:\n  hello: \"a \\\"greeting\\\"\"\nweights:\n  hello: 2\n  world: 1\nnum_areas: 0\nseed: 0\niters: 1\n";
+        assert_eq!(expected, format!("{}", gen));
+        assert_eq!(2, gen.weight(0));
+        assert_eq!(None, gen.clue(1));
+    }
+
     #[test]
     fn test_gen_iter () {
         let words = vec![
@@ -247,6 +671,115 @@ pub mod tests {
         });
     }
 
+    #[test]
+    fn budget() {
+        let words = vec![
+            "toon",
+            "took",
+            "noob",
+            "koob"
+        ];
+        test_generator(words.clone(), 0, &|gen| {
+            let gen = gen.with_budget(Budget { max_results: Some(5), ..Budget::default() });
+            assert_eq!(5, gen.iter().count());
+            assert_eq!(StopReason::MaxResults, gen.stop_reason());
+        });
+        test_generator(words.clone(), 0, &|gen| {
+            let gen = gen.with_budget(Budget { max_nodes: Some(10), ..Budget::default() });
+            gen.iter().count();
+            assert_eq!(StopReason::MaxNodes, gen.stop_reason());
+        });
+        test_generator(words.clone(), 0, &|gen| {
+            use std::time::Duration;
+            let gen = gen.with_budget(Budget { timeout: Some(Duration::from_secs(0)), ..Budget::default() });
+            assert_eq!(0, gen.iter().count());
+            assert_eq!(StopReason::Timeout, gen.stop_reason());
+        });
+        test_generator(words, 0, &|gen| {
+            let gen = gen.with_budget(Budget { max_results: Some(100), ..Budget::default() });
+            assert_eq!(22, gen.iter().count());
+            assert_eq!(StopReason::Exhausted, gen.stop_reason());
+        });
+    }
+
+    #[test]
+    fn stats() {
+        let words = vec!["toon", "took", "noob", "koob"];
+        test_generator(words.clone(), 0, &|gen| {
+            assert_eq!(Stats::default(), gen.stats());
+            assert_eq!(22, gen.iter().count());
+            let stats = gen.stats();
+            assert_eq!(22, stats.solutions);
+            assert!(stats.nodes > stats.solutions);
+            assert!(stats.collisions > 0 && stats.seen_rejections > 0);
+            // nothing to keep to without num_areas
+            assert_eq!(0, stats.area_rejections);
+        });
+        test_generator(words, 1, &|gen| {
+            gen.iter().count();
+            assert!(gen.stats().area_rejections > 0);
+        });
+    }
+
+    #[test]
+    fn seen_set() {
+        use seen::SeenSet;
+        let words = vec!["toon", "took", "noob", "koob"];
+        let run = |seen: SeenSet, max_memory: Option<usize>| {
+            let gen = Generator::with_config(&words, Config { seen, max_memory, ..Config::default() }).unwrap();
+            let crosswords: Vec<_> = gen.iter().collect();
+            (crosswords, gen.stats())
+        };
+        let (expected, stats) = run(SeenSet::Exact, None);
+        assert!(stats.seen.entries > 0 && stats.seen.bytes > 0);
+        assert_eq!(0, stats.seen.dropped);
+        let (crosswords, stats) = run(SeenSet::Fingerprint, None);
+        assert_eq!(expected, crosswords);
+        assert!(stats.seen.bytes > 0);
+        let (crosswords, stats) = run(SeenSet::Bloom { false_positive_rate: 1e-6 }, Some(1 << 16));
+        assert_eq!(expected, crosswords);
+        assert_eq!(1 << 16, stats.seen.bytes);
+
+        // nothing fits, so layouts reached by placing the words in another order are
+        // found again
+        let (crosswords, stats) = run(SeenSet::Exact, Some(1));
+        assert!(crosswords.len() > expected.len());
+        assert_eq!(0, stats.seen.entries);
+        assert!(stats.seen.dropped > 0);
+    }
+
+    #[test]
+    fn crossings() {
+        use rand::rand_range;
+        // the placements where the letters match, in the order that trying each of
+        // the layout's letters with each letter of the word gives, without the rest
+        let words = vec!["scent", "scarf", "fleet", "tenet", "eerie", "toon", "note", "bent"];
+        let gen = Generator::new(&words, 0, 0).unwrap();
+        let crossword = gen.iter().next().unwrap();
+        let letters = crossword.letters();
+        let grid = GridLetters::new(letters);
+        let seed = 7;
+        let (mut num_pairs, mut num_crossings) = (0, 0);
+        for word_index in 0..words.len() {
+            let w = gen.candidate(&[word_index], 0);
+            let mut expected = vec![];
+            for letter_rank in 0..letters.len() {
+                let letter = rand_range(letters.len(), hash(w, seed))(letter_rank);
+                for char_rank in 0..w.1 {
+                    let i2 = rand_range(w.1, hash(letters[letter], seed))(char_rank);
+                    if gen.word_chars(word_index)[i2] == letters[letter].0 {
+                        expected.push(((letter_rank, char_rank), letter, i2));
+                    }
+                    num_pairs += 1;
+                }
+            }
+            let crossings = gen.crossings(w, letters, &grid, seed);
+            assert_eq!(expected, crossings);
+            num_crossings += crossings.len();
+        }
+        assert_eq!((259, 17), (num_pairs, num_crossings));
+    }
+
     #[test]
     fn letter_block_collision() {
         let words = vec![
@@ -260,4 +793,223 @@ pub mod tests {
             assert_eq!(0, gen.iter().count());
         });
     }
+
+    #[test]
+    fn invalid_words() {
+        let words: Vec<&str> = vec![];
+        assert_eq!(Some(CrosswordError::EmptyWordList), Generator::new(&words, 0, 0).err());
+        assert_eq!(Some(CrosswordError::WordTooShort { index: 1, word: "a".to_string() }),
+            Generator::new(&["ton", "a"], 0, 0).err());
+        let long_word: String = std::iter::repeat_n('o', 61).collect();
+        assert_eq!(Some(CrosswordError::WordTooLong { index: 0, word: long_word.clone() }),
+            Generator::new(&[long_word], 0, 0).err());
+    }
+
+    #[test]
+    fn stays_in_bounds() {
+        // a chain of 50 letter words that only share their first and last letters
+        // would run past ±MAX_COORD at the third word
+        let words: Vec<String> = (0..4u32).map(|i| {
+            let end = |j: u32| ::std::char::from_u32('A' as u32 + j).unwrap();
+            let middle = (0..48).map(|j| ::std::char::from_u32(0x100 + i * 48 + j).unwrap());
+            Some(end(i)).into_iter().chain(middle).chain(Some(end(i + 1))).collect()
+        }).collect();
+        test_generator(words.iter().map(|word| &word[..]).collect(), 0, &|gen| {
+            assert_eq!(0, gen.iter().count());
+        });
+    }
+
+    #[test]
+    fn partial() {
+        let words = vec!["toon", "took", "noob", "koob", "zzz"];
+        test_generator(words.clone(), 0, &|gen| {
+            assert_eq!(0, gen.iter().count());
+        });
+        let gen = Generator::with_config(&words, Config { partial: true, ..Config::default() }).unwrap();
+        let crosswords: Vec<_> = gen.iter().collect();
+        assert_eq!(22, crosswords.len());
+        for crossword in &crosswords {
+            assert_eq!(vec!["zzz"], gen.left_out(crossword).collect::<Vec<_>>());
+        }
+
+        // by and bx can't both cross ab, and by is worth more. bx anchors a search
+        // too, and crosses by without ab
+        let entries = vec![
+            Entry::new("ab"),
+            Entry::new("bx"),
+            Entry { weight: 5, ..Entry::new("by") }
+        ];
+        let gen = Generator::from_entries(&entries, Config { partial: true, ..Config::default() }).unwrap();
+        let crosswords: Vec<_> = gen.iter().collect();
+        let weights: Vec<_> = crosswords.iter().map(|crossword| gen.placed_weight(crossword)).collect();
+        assert_eq!(vec![2, 6, 6], weights);
+        assert_eq!(vec!["bx"], gen.left_out(&crosswords[1]).collect::<Vec<_>>());
+        assert_eq!(vec!["ab"], gen.left_out(&crosswords[2]).collect::<Vec<_>>());
+        let gen = Generator::from_entries(&entries, Config { partial: true, num_iters: 2, ..Config::default() }).unwrap();
+        assert_eq!(3, gen.multi_iter(2).count());
+    }
+
+    #[test]
+    fn partial_when_every_word_fits() {
+        // layouts whose words left were only turned down as seen aren't dead ends, and
+        // once a layout places every word no partial one is worth yielding
+        let words = vec!["toon", "took", "noob", "koob"];
+        let expected: Vec<_> = Generator::new(&words, 0, 0).unwrap().iter().collect();
+        assert_eq!(22, expected.len());
+        let with_partial = || Generator::with_config(&words, Config { partial: true, ..Config::default() }).unwrap();
+        assert_eq!(expected, with_partial().iter().collect::<Vec<_>>());
+        assert_eq!(expected, with_partial().multi_iter(1).collect::<Vec<_>>());
+        // beam search reaches layouts with fewer words first
+        let config = Config { partial: true, strategy: Strategy::Beam { width: 1000 }, ..Config::default() };
+        assert_eq!(22, Generator::with_config(&words, config).unwrap().iter().count());
+    }
+
+    #[test]
+    fn anchors() {
+        use std::collections::HashSet;
+        let words = vec!["toon", "took", "noob", "koob"];
+        let with_anchor = |anchor: Anchor| Generator::with_config(&words, Config { anchor, ..Config::default() }).unwrap();
+        let gen = with_anchor(Anchor::Longest);
+        assert_eq!(vec![0], gen.anchors());
+        let gen = Generator::with_config(&["ab", "abc", "cb"], Config { anchor: Anchor::Longest, ..Config::default() }).unwrap();
+        for crossword in gen.iter() {
+            assert_eq!(Some(START_POSITION), crossword.positions.get(1));
+        }
+
+        // every layout places the first word one way or the other, so the other
+        // anchors only find the same layouts translated or transposed
+        let first: HashSet<_> = with_anchor(Anchor::First).iter().map(|crossword| crossword.positions.canonical()).collect();
+        let gen = with_anchor(Anchor::Every);
+        assert_eq!(vec![0, 1, 2, 3], gen.anchors());
+        let every: Vec<_> = gen.iter().map(|crossword| crossword.positions.canonical()).collect();
+        assert_eq!(22, every.len());
+        assert_eq!(first, every.into_iter().collect());
+        assert_eq!(22, with_anchor(Anchor::Every).multi_iter(2).count());
+    }
+
+    #[test]
+    fn dedup() {
+        use std::collections::HashSet;
+        use word_placements::Dedup;
+        let words = vec!["toon", "took", "noob", "koob"];
+        let count = |anchor: Anchor, dedup: Option<Dedup>| {
+            let config = Config { anchor, dedup, ..Config::default() };
+            let crosswords: Vec<_> = Generator::with_config(&words, config).unwrap().iter().collect();
+            let canonical: HashSet<_> = crosswords.iter().map(|crossword| crossword.positions.canonical()).collect();
+            (crosswords.len(), canonical.len())
+        };
+        // from each anchor, exact finds the layouts found from earlier anchors again,
+        // moved or transposed
+        let (num_exact, num_distinct) = count(Anchor::Every, Some(Dedup::Exact));
+        assert!(num_exact > 22);
+        assert_eq!(22, num_distinct);
+        assert_eq!((22, 22), count(Anchor::Every, None));
+        assert_eq!((22, 22), count(Anchor::Every, Some(Dedup::Canonical)));
+        // one anchor is always placed across at the start position, so no two of its
+        // layouts are moved or transposed copies
+        assert_eq!((22, 22), count(Anchor::First, Some(Dedup::Exact)));
+        assert_eq!((22, 22), count(Anchor::First, Some(Dedup::Canonical)));
+    }
+
+    #[test]
+    fn dedup_fixed_orientation() {
+        use std::collections::HashSet;
+        use word_placements::Dedup;
+        use size::{SizeLimit, Orientation};
+        // a layout on the way can fit the frame when its transpose doesn't, so
+        // canonical dedup can't count that transpose as seen
+        let words = vec!["scent", "scarf", "fleet", "tenet", "eerie"];
+        let size = SizeLimit { max_width: Some(7), max_height: Some(9), orientation: Orientation::Fixed, ..SizeLimit::default() };
+        let layouts = |dedup: Dedup| {
+            let config = Config { anchor: Anchor::Every, dedup: Some(dedup), size, ..Config::default() };
+            let crosswords: Vec<_> = Generator::with_config(&words, config).unwrap().iter().collect();
+            let translated: HashSet<_> = crosswords.iter().map(|crossword| crossword.positions.translated()).collect();
+            (crosswords.len(), translated)
+        };
+        let (num_exact, exact) = layouts(Dedup::Exact);
+        assert!(num_exact > exact.len());
+        assert_eq!(183, exact.len());
+        let (num_canonical, canonical) = layouts(Dedup::Canonical);
+        assert_eq!(exact.len(), num_canonical);
+        assert_eq!(exact, canonical);
+    }
+
+    #[test]
+    fn size_limit() {
+        use size::{SizeLimit, Orientation};
+        let words = vec!["toon", "took", "noob", "koob"];
+        let all: Vec<_> = Generator::new(&words, 0, 0).unwrap().iter().collect();
+        let limit = SizeLimit { max_width: Some(4), max_height: Some(6), ..SizeLimit::default() };
+        let exact = SizeLimit { max_width: Some(5), max_height: Some(5), exact: true, ..SizeLimit::default() };
+        for &(size, count) in &[(limit, 9), (SizeLimit { orientation: Orientation::Fixed, ..limit }, 5), (exact, 3)] {
+            // pruning early finds the same layouts, in the same order, as filtering afterwards
+            let expected: Vec<_> = all.iter()
+                .filter(|crossword| size.fits(crossword.bounding_box()) && size.fills(crossword.bounding_box()))
+                .cloned()
+                .collect();
+            assert_eq!(count, expected.len());
+            let gen = Generator::with_config(&words, Config { size, ..Config::default() }).unwrap();
+            assert_eq!(expected, gen.iter().collect::<Vec<_>>());
+        }
+        // the anchor alone doesn't fit
+        let size = SizeLimit { max_width: Some(3), max_height: Some(3), ..SizeLimit::default() };
+        assert_eq!(0, Generator::with_config(&words, Config { size, partial: true, ..Config::default() }).unwrap().iter().count());
+    }
+
+    #[test]
+    fn locked() {
+        use std::collections::HashSet;
+        let words = vec!["toon", "took", "noob", "koob"];
+        let noob = Position { row: 3, col: 2, dir: Vertical };
+        let gen = Generator::new(&words, 0, 0).unwrap().with_locked(&[("noob", noob)]).unwrap();
+        assert!(gen.is_locked(2) && !gen.is_locked(0));
+        let crosswords: Vec<_> = gen.iter().collect();
+        assert!(crosswords.iter().all(|crossword| crossword.positions.get(2) == Some(noob)));
+        // the same layouts as without locking, moved to where noob is locked
+        let expected: HashSet<_> = Generator::new(&words, 0, 0).unwrap().iter().map(|crossword| crossword.positions.canonical()).collect();
+        assert_eq!(expected, crosswords.iter().map(|crossword| crossword.positions.canonical()).collect());
+        assert_eq!(22, crosswords.len());
+
+        let lock = |locked: &[(&str, Position)]| Generator::new(&words, 0, 0).unwrap().with_locked(locked).map(|_| ());
+        assert_eq!(Err(CrosswordError::UnknownLockedWord { word: "zoon".to_string() }), lock(&[("zoon", noob)]));
+        assert_eq!(Err(CrosswordError::UnknownLockedWord { word: "noob".to_string() }), lock(&[("noob", noob), ("noob", START_POSITION)]));
+        match lock(&[("noob", noob), ("koob", Position { row: 3, col: 2, dir: Horizontal })]) {
+            Err(CrosswordError::Collision { index, .. }) => assert_eq!(3, index),
+            other => panic!("expected a Collision, got {:?}", other)
+        }
+        // every word locked is a finished layout
+        let all = [("toon", Position { row: 0, col: 0, dir: Horizontal }), ("took", Position { row: 0, col: 0, dir: Vertical }),
+            ("noob", Position { row: 0, col: 3, dir: Vertical }), ("koob", Position { row: 3, col: 0, dir: Horizontal })];
+        let gen = Generator::new(&words, 0, 0).unwrap().with_locked(&all).unwrap();
+        assert_eq!(1, gen.iter().count());
+    }
+
+    #[test]
+    fn score() {
+        use score::{Score, Metric, Weighted};
+        let words = vec!["toon", "took", "noob", "koob"];
+        let all: Vec<_> = Generator::new(&words, 0, 0).unwrap().iter().collect();
+
+        // each layout is in the top 1 by area when it's found, so the areas only go down
+        let config = Config { num_areas: 1, score: Some(Weighted { terms: vec![(Metric::Area, 1.0)] }), ..Config::default() };
+        let gen = Generator::with_config(&words, config).unwrap();
+        let costs: Vec<_> = gen.iter().map(|crossword| gen.score(&crossword).unwrap()).collect();
+        assert!(costs.windows(2).all(|pair| pair[0] >= pair[1]));
+        assert_eq!(16.0, costs[costs.len() - 1]);
+
+        struct Height;
+        impl Score for Height {
+            fn cost(&self, crossword: &Crossword) -> f64 {
+                -(crossword.bounding_box().height() as f64)
+            }
+            fn name(&self) -> String {
+                "height".to_string()
+            }
+        }
+        let gen = Generator::new(&words, 1, 0).unwrap().with_score(Arc::new(Height));
+        let tallest = all.iter().map(|crossword| crossword.bounding_box().height()).max().unwrap();
+        let crosswords: Vec<_> = gen.iter().collect();
+        assert_eq!(tallest, crosswords[crosswords.len() - 1].bounding_box().height());
+        assert_eq!(Some(-(tallest as f64)), gen.score(&crosswords[crosswords.len() - 1]));
+    }
 }
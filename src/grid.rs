@@ -18,80 +18,100 @@ impl Grid {
             num_overlaps: 0,
             grid: Grid::make_grid(bb),
             letters: vec![],
-            bb: bb
+            bb
         }
     }
     fn make_grid(bb: BoundingBox) -> Vec<GridCell> {
-        vec![Empty; ((bb.width() as usize) * (bb.height() as usize))]
+        vec![Empty; (bb.width() as usize) * (bb.height() as usize)]
     }
     pub fn set(&self, word: &str, word_len: usize, pos: Position) -> Grid {
-        let bb = self.bb.combine(BoundingBox::from_word_pos(word_len, pos).expand());
-        let mut grid = Grid::make_grid(bb);
-        let mut letters = self.letters.clone();
-        let mut is_valid = self.is_valid;
-        let mut num_overlaps = self.num_overlaps;
+        // an empty grid has nothing to keep, so its bounding box doesn't stretch the new one
+        let is_empty = self.letters.is_empty() && self.grid.iter().all(|&cell| cell == Empty);
+        let word_bb = BoundingBox::from_word_pos(word_len, pos).expand();
+        let bb = if is_empty { word_bb } else { self.bb.combine(word_bb) };
+        let mut grid = Grid {
+            is_valid: self.is_valid,
+            num_overlaps: self.num_overlaps,
+            grid: Grid::make_grid(bb),
+            letters: self.letters.clone(),
+            bb
+        };
         // copy old cells to new grid
-        for (i, &cell) in self.grid.iter().enumerate() {
-            let (row, col) = self.bb.row_col_inverse(i);
-            let row_col = bb.row_col(row, col);
-            grid[row_col] = cell
+        if !is_empty {
+            for (i, &cell) in self.grid.iter().enumerate() {
+                let (row, col) = self.bb.row_col_inverse(i);
+                let row_col = bb.row_col(row, col);
+                grid.grid[row_col] = cell
+            }
+        }
+        grid.add(word, word_len, pos);
+        grid
+    }
+    // the same grid as setting each word in turn, but sized for all of them up front
+    // so the cells are only copied once
+    pub fn from_words(words: &[(&str, usize, Position)]) -> Grid {
+        let bb = words.iter()
+            .map(|&(_, word_len, pos)| BoundingBox::from_word_pos(word_len, pos).expand())
+            .fold(None, |bb: Option<BoundingBox>, word_bb| Some(bb.map_or(word_bb, |bb| bb.combine(word_bb))));
+        let mut grid = Grid::new(bb.unwrap_or(BoundingBox::new(0, 0, 0, 0)));
+        for &(word, word_len, pos) in words {
+            grid.add(word, word_len, pos);
         }
+        grid
+    }
+    // adds a word the bounding box already covers
+    fn add(&mut self, word: &str, word_len: usize, pos: Position) {
         // add word and check for collisions and overlaps and letter additions/removals
         for  (cell, (row, col)) in GridCell::from_word(word, word_len, pos) {
-            let row_col = bb.row_col(row, col);
-            let old_cell = grid[row_col];
+            let row_col = self.bb.row_col(row, col);
+            let old_cell = self.grid[row_col];
             let next_cell = old_cell.get_next(cell);
-            grid[row_col] = next_cell;
+            self.grid[row_col] = next_cell;
             match next_cell {
                 Letter(c, Some(dir)) => {
                     // add letter
-                    letters.push((c, Position {row: row, col: col, dir: dir}));
+                    self.letters.push((c, Position {row, col, dir}));
                 },
                 Letter(_, None) => if let Letter(_, _) = old_cell {
                     // remove letter
-                    letters.iter()
+                    self.letters.iter()
                         .position(|&(_, pos)| (pos.row, pos.col) == (row, col) )
-                        .map(|i| letters.remove(i));
+                        .map(|i| self.letters.remove(i));
                     if let Letter(_, _) = cell {
                         // add overlap
-                        num_overlaps += 1;
+                        self.num_overlaps += 1;
                     }
                 },
                 Collision => {
-                    is_valid = false
+                    self.is_valid = false
                 },
                 _ => {}
             }
         }
-        Grid {
-            is_valid: is_valid,
-            num_overlaps: num_overlaps,
-            grid: grid,
-            letters: letters,
-            bb: bb
-        }
     }
     pub fn can_add_word(&self, word: &str, word_len: usize, pos: Position) -> bool {
         GridCell::from_word(word, word_len, pos).all(|(cell, (row, col))| {
-            if row < self.bb.top || col < self.bb.left {
+            // nothing outside the grid to collide with. past the right edge, row_col
+            // would wrap around to the next row
+            if row < self.bb.top || col < self.bb.left || row > self.bb.bottom || col > self.bb.right {
                 return true
             }
             let row_col = self.bb.row_col(row, col);
-            if row_col >= self.grid.len() {
-                return true
-            }
             let old_cell = self.grid[row_col];
             let next_cell = old_cell.get_next(cell);
             next_cell != Collision
         })
     }
+    pub fn num_letters(&self) -> usize {
+        self.grid.iter().filter(|&&cell| matches!(cell, Letter(_, _))).count()
+    }
     pub fn iter_rows<'a>(&'a self) -> impl Iterator<Item=Option<GridCell>> + 'a {
         let bb = self.bb.contract();
         (bb.top .. bb.bottom + 1).flat_map(move |row| {
             (bb.left .. bb.right + 1).map(move |col| {
                 let row_col = self.bb.row_col(row, col);
                 Some(self.grid[row_col])
-            }).chain(if row < bb.bottom { Some(None) } else { None }.into_iter())
+            }).chain(if row < bb.bottom { Some(None) } else { None })
         })
     }
     pub fn iter_cols<'a>(&'a self) -> impl Iterator<Item=Option<GridCell>> + 'a {
@@ -100,7 +120,7 @@ impl Grid {
             (bb.top .. bb.bottom + 1).map(move |row| {
                 let row_col = self.bb.row_col(row, col);
                 Some(self.grid[row_col])
-            }).chain(if col < bb.right { Some(None) } else { None }.into_iter())
+            }).chain(if col < bb.right { Some(None) } else { None })
         })
     }
 }
@@ -111,7 +131,7 @@ impl Display for Grid {
         for entry in self.iter_rows() {
             match entry {
                 Some(cell) => write!(f, "{}", cell)?,
-                None => writeln!(f, "")?
+                None => writeln!(f)?
             }
         }
         write!(f, "")
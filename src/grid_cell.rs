@@ -60,7 +60,7 @@ impl GridCell {
             (Letter(c1, Some(o1)), Letter(c2, Some(o2))) if c1 == c2 && o1 != o2 => {
                 Letter(c1, None)
             },
-            (Letter(c, opt_o1 @ _), Block(opt_o2 @ _)) | (Block(opt_o2 @ _), Letter(c, opt_o1 @ _)) => {
+            (Letter(c, opt_o1), Block(opt_o2)) | (Block(opt_o2), Letter(c, opt_o1)) => {
                 match (opt_o1, opt_o2) {
                     (Some(o1), Some(o2)) if o1 != o2 => Letter(c, None),
                     (None, Some(_)) => Letter(c, None),
@@ -0,0 +1,237 @@
+//! Reading word lists from bytes and files.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::prelude::*;
+
+use normalize::{Normalizer, compose};
+use word_list::{Entry, WordListError, parse_line};
+
+/// How a [`Reader`](struct.Reader.html) reads word lists.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct InputOptions {
+    /// Lowercase the words, so words that differ only by case are duplicates.
+    pub fold_case: bool,
+    /// Replace invalid UTF-8 with U+FFFD and report it, instead of rejecting the input.
+    pub lossy: bool
+}
+
+/// Something about the input worth telling the user, that didn't stop the read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Notice {
+    /// Invalid UTF-8 on a line was replaced with U+FFFD.
+    Lossy {
+        /// Where the line was read from.
+        source: String,
+        /// The line number, from 1.
+        line: usize
+    },
+    /// A word was skipped as it normalizes to a word read before it.
+    Duplicate {
+        /// Where the line was read from.
+        source: String,
+        /// The line number, from 1.
+        line: usize,
+        /// The normalized word.
+        word: String,
+        /// Where the first one was read from.
+        first_source: String,
+        /// The line number of the first one.
+        first_line: usize
+    }
+}
+use std::fmt::{Display, Formatter, Result as FmtResult};
+impl Display for Notice {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match *self {
+            Notice::Lossy { ref source, line } => {
+                write!(f, "{}:{}: invalid UTF-8 replaced with U+FFFD", source, line)
+            },
+            Notice::Duplicate { ref source, line, ref word, ref first_source, first_line } => {
+                write!(f, "{}:{}: skipped duplicate word '{}', first seen at {}:{}", source, line, word, first_source, first_line)
+            }
+        }
+    }
+}
+
+/// Decodes, normalizes and deduplicates word list lines. Duplicates are detected
+/// across everything read since the last reset, by normalized word.
+pub struct Reader {
+    options: InputOptions,
+    normalizer: Normalizer,
+    seen: HashMap<String, (String, usize)>,
+    notices: Vec<Notice>
+}
+impl Reader {
+    /// A reader that hasn't seen any words yet.
+    pub fn new(options: InputOptions) -> Reader {
+        Reader {
+            options,
+            normalizer: Normalizer::new(options.fold_case),
+            seen: HashMap::new(),
+            notices: vec![]
+        }
+    }
+    /// What the reads so far had to report, in order.
+    pub fn notices(&self) -> &[Notice] {
+        &self.notices
+    }
+    /// Forgets the words seen so far, e.g. between the word lists of a batch.
+    pub fn reset(&mut self) {
+        self.seen.clear();
+    }
+
+    /// Decodes a line of bytes, taking off the line ending and, on the first line, a
+    /// UTF-8 byte order mark.
+    pub fn decode(&mut self, source: &str, line_number: usize, bytes: &[u8]) -> Result<String, WordListError> {
+        let mut bytes = bytes;
+        while bytes.last() == Some(&b'\n') || bytes.last() == Some(&b'\r') {
+            bytes = &bytes[..bytes.len() - 1];
+        }
+        if line_number == 1 && bytes.starts_with(b"\xef\xbb\xbf") {
+            bytes = &bytes[3..];
+        }
+        match ::std::str::from_utf8(bytes) {
+            Ok(line) => Ok(line.to_string()),
+            Err(e) => {
+                if !self.options.lossy {
+                    return Err(WordListError::InvalidUtf8 {
+                        source: source.to_string(),
+                        line: line_number,
+                        column: e.valid_up_to() + 1
+                    })
+                }
+                self.notices.push(Notice::Lossy { source: source.to_string(), line: line_number });
+                Ok(String::from_utf8_lossy(bytes).into_owned())
+            }
+        }
+    }
+
+    /// Decodes, parses and normalizes a line. Returns None for blank lines, comments
+    /// and duplicates.
+    pub fn read_line(&mut self, source: &str, line_number: usize, bytes: &[u8]) -> Result<Option<Entry>, WordListError> {
+        let line = self.decode(source, line_number, bytes)?;
+        let entry = match parse_line(source, line_number, &line)? {
+            Some(entry) => entry,
+            None => return Ok(None)
+        };
+        let entry = Entry {
+            word: self.normalizer.normalize(&entry.word),
+            clue: entry.clue.map(|clue| compose(&clue)),
+            ..entry
+        };
+        if let Some(&(ref first_source, first_line)) = self.seen.get(&entry.word) {
+            self.notices.push(Notice::Duplicate {
+                source: source.to_string(),
+                line: line_number,
+                word: entry.word.clone(),
+                first_source: first_source.clone(),
+                first_line
+            });
+            return Ok(None)
+        }
+        self.seen.insert(entry.word.clone(), (source.to_string(), line_number));
+        Ok(Some(entry))
+    }
+
+    /// Reads a word list, one entry per line.
+    pub fn read(&mut self, source: &str, bytes: &[u8]) -> Result<Vec<Entry>, WordListError> {
+        let mut entries = vec![];
+        for (i, line) in bytes.split(|&b| b == b'\n').enumerate() {
+            if let Some(entry) = self.read_line(source, i + 1, line)? {
+                entries.push(entry);
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Reads word lists separated by blank lines, with duplicates detected per list.
+    pub fn read_batch(&mut self, source: &str, bytes: &[u8]) -> Result<Vec<Vec<Entry>>, WordListError> {
+        let mut batch = vec![];
+        let mut entries = vec![];
+        for (i, line) in bytes.split(|&b| b == b'\n').enumerate() {
+            if line.iter().all(|b| b" \t\r".contains(b)) {
+                if !entries.is_empty() {
+                    batch.push(entries);
+                    entries = vec![];
+                }
+                self.reset();
+                continue
+            }
+            if let Some(entry) = self.read_line(source, i + 1, line)? {
+                entries.push(entry);
+            }
+        }
+        if !entries.is_empty() {
+            batch.push(entries);
+        }
+        Ok(batch)
+    }
+
+    /// Reads a word list from a file.
+    pub fn load(&mut self, path: &str) -> Result<Vec<Entry>, WordListError> {
+        let bytes = read_file(path)?;
+        self.read(path, &bytes)
+    }
+}
+
+fn read_file(path: &str) -> Result<Vec<u8>, WordListError> {
+    let mut bytes = vec![];
+    File::open(path).and_then(|mut file| file.read_to_end(&mut bytes)).map_err(|e| WordListError::Io {
+        source: path.to_string(),
+        reason: e.to_string()
+    })?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_and_dedups() {
+        let mut reader = Reader::new(InputOptions { fold_case: true, lossy: false });
+        let entries = reader.read("a", b"  Cafe\xcc\x81 \r\ncaf\xc3\xa9\tdup\nTea\n").unwrap();
+        assert_eq!(vec![Entry::new("caf\u{e9}"), Entry::new("tea")], entries);
+        assert_eq!(&[Notice::Duplicate {
+            source: "a".to_string(),
+            line: 2,
+            word: "caf\u{e9}".to_string(),
+            first_source: "a".to_string(),
+            first_line: 1
+        }], reader.notices());
+
+        // duplicates are detected across sources until reset
+        assert_eq!(0, reader.read("b", b"tea").unwrap().len());
+        reader.reset();
+        assert_eq!(1, reader.read("b", b"tea").unwrap().len());
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let mut reader = Reader::new(InputOptions::default());
+        assert_eq!(Err(WordListError::InvalidUtf8 { source: "a".to_string(), line: 2, column: 3 }),
+            reader.read("a", b"ok\nno\xff\n"));
+
+        let mut reader = Reader::new(InputOptions { fold_case: false, lossy: true });
+        assert_eq!(vec![Entry::new("ok"), Entry::new("no\u{fffd}")], reader.read("a", b"ok\nno\xff\n").unwrap());
+        assert_eq!(&[Notice::Lossy { source: "a".to_string(), line: 2 }], reader.notices());
+    }
+
+    #[test]
+    fn batch() {
+        let mut reader = Reader::new(InputOptions::default());
+        let batch = reader.read_batch("a", b"ton\ntok\n \nton\n\n").unwrap();
+        assert_eq!(vec![vec![Entry::new("ton"), Entry::new("tok")], vec![Entry::new("ton")]], batch);
+        assert!(reader.notices().is_empty());
+    }
+
+    #[test]
+    fn missing_file() {
+        let mut reader = Reader::new(InputOptions::default());
+        match reader.load("does/not/exist.tsv") {
+            Err(WordListError::Io { source, .. }) => assert_eq!("does/not/exist.tsv", source),
+            other => panic!("expected an Io error, got {:?}", other)
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+
+use placement::Position;
+
+/// Where each letter appears in the word list, so a search only tries to cross
+/// a layout's letters with the letters of a word that match them.
+#[derive(Debug, Clone, Default)]
+pub struct LetterIndex {
+    // each letter's word index and offset in the word, by word index then offset
+    by_letter: HashMap<char, Vec<(usize, usize)>>,
+    // each word's distinct letters
+    word_letters: Vec<Vec<char>>
+}
+impl LetterIndex {
+    pub fn new(word_chars_list: &[Vec<char>]) -> LetterIndex {
+        let mut by_letter: HashMap<char, Vec<(usize, usize)>> = HashMap::new();
+        let mut word_letters = vec![];
+        for (word_index, word_chars) in word_chars_list.iter().enumerate() {
+            let mut letters = vec![];
+            for (offset, &c) in word_chars.iter().enumerate() {
+                by_letter.entry(c).or_default().push((word_index, offset));
+                if !letters.contains(&c) {
+                    letters.push(c);
+                }
+            }
+            word_letters.push(letters);
+        }
+        LetterIndex { by_letter, word_letters }
+    }
+    // the word index and offset of each c in the word, in order
+    pub fn offsets(&self, word_index: usize, c: char) -> &[(usize, usize)] {
+        match self.by_letter.get(&c) {
+            Some(entries) => {
+                let start = entries.partition_point(|&(i, _)| i < word_index);
+                let end = entries.partition_point(|&(i, _)| i <= word_index);
+                &entries[start..end]
+            },
+            None => &[]
+        }
+    }
+    // whether the word has any of a layout's letters, so it can cross it
+    pub fn crosses(&self, word_index: usize, grid: &GridLetters) -> bool {
+        self.word_letters[word_index].iter().any(|c| grid.0.contains_key(c))
+    }
+    // each index into the layout's letters and offset in the word where the word
+    // can cross it: only the pairs whose letters match
+    pub fn crossings<'a>(&'a self, word_index: usize, grid: &'a GridLetters) -> impl Iterator<Item=(usize, usize)> + 'a {
+        self.word_letters[word_index].iter().flat_map(move |&c| {
+            let letters = grid.0.get(&c).map_or(&[][..], |letters| &letters[..]);
+            let offsets = self.offsets(word_index, c);
+            letters.iter().flat_map(move |&letter| offsets.iter().map(move |&(_, offset)| (letter, offset)))
+        })
+    }
+}
+
+/// The indexes of a layout's letters, by letter.
+#[derive(Debug, Clone, Default)]
+pub struct GridLetters(HashMap<char, Vec<usize>>);
+impl GridLetters {
+    pub fn new(letters: &[(char, Position)]) -> GridLetters {
+        let mut by_letter: HashMap<char, Vec<usize>> = HashMap::new();
+        for (i, &(c, _)) in letters.iter().enumerate() {
+            by_letter.entry(c).or_default().push(i);
+        }
+        GridLetters(by_letter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use placement::START_POSITION;
+
+    fn chars(words: &[&str]) -> Vec<Vec<char>> {
+        words.iter().map(|word| word.chars().collect()).collect()
+    }
+
+    #[test]
+    fn offsets() {
+        let index = LetterIndex::new(&chars(&["toon", "noob", "kob"]));
+        assert_eq!(&[(0, 1), (0, 2)], index.offsets(0, 'o'));
+        assert_eq!(&[(1, 1), (1, 2)], index.offsets(1, 'o'));
+        assert_eq!(&[(2, 1)], index.offsets(2, 'o'));
+        assert!(index.offsets(0, 'b').is_empty());
+        assert!(index.offsets(0, 'z').is_empty());
+    }
+
+    #[test]
+    fn crosses() {
+        let index = LetterIndex::new(&chars(&["toon", "kid"]));
+        let grid = GridLetters::new(&[('n', START_POSITION), ('o', START_POSITION)]);
+        assert_eq!(2, grid.0.len());
+        assert!(index.crosses(0, &grid));
+        assert!(!index.crosses(1, &grid));
+    }
+
+    #[test]
+    fn crossings() {
+        // noob's letters n, o, o, b under toon: only the 5 matching pairs out of the 16
+        let index = LetterIndex::new(&chars(&["toon", "noob"]));
+        let letters: Vec<_> = "noob".chars().map(|c| (c, START_POSITION)).collect();
+        let grid = GridLetters::new(&letters);
+        let mut crossings: Vec<_> = index.crossings(0, &grid).collect();
+        crossings.sort();
+        assert_eq!(vec![(0, 3), (1, 1), (1, 2), (2, 1), (2, 2)], crossings);
+        assert_eq!(0, index.crossings(1, &GridLetters::new(&[('z', START_POSITION)])).count());
+    }
+}
@@ -0,0 +1,99 @@
+//! Generates compact crossword layouts from a list of words.
+//!
+//! A [`Generator`](generate/struct.Generator.html) searches for every way to
+//! interlock all of its words, yielding each layout as a
+//! [`Crossword`](crossword/struct.Crossword.html). It owns its words and is
+//! `Send + Sync`, so it can live in a long-running service:
+//!
+//! ```
+//! extern crate crossword;
+//! use crossword::{Generator, Config};
+//!
+//! # fn main() {
+//! let gen = Generator::with_config(&["ton", "tok", "nob", "kob"], Config::default()).unwrap();
+//! for crossword in gen.iter() {
+//!     // YAML with the positions, size and a rendering of the grid
+//!     println!("{}", crossword);
+//! }
+//! # }
+//! ```
+//!
+//! To search on several threads, share the generator with a
+//! [`ParIter`](parallel/struct.ParIter.html):
+//! `ParIter::new(Arc::new(gen), num_threads)`.
+//!
+//! A layout can also be built from known [`Position`](placement/struct.Position.html)s,
+//! to validate or render it:
+//!
+//! ```
+//! extern crate crossword;
+//! use crossword::{Crossword, Position};
+//! use crossword::Direction::{Horizontal, Vertical};
+//!
+//! # fn main() {
+//! let crossword = Crossword::from_positions(&["hello", "world"], &[
+//!     Position { row: 3, col: 0, dir: Horizontal },
+//!     Position { row: 0, col: 2, dir: Vertical }
+//! ]).unwrap();
+//! assert!(crossword.is_valid());
+//! assert_eq!(25, crossword.bounding_box().area());
+//! # }
+//! ```
+//!
+//! Constructors and validators return a [`CrosswordError`](error/enum.CrosswordError.html)
+//! saying what was wrong with their input, rather than panicking.
+//!
+//! Word lists with clues and weights are read with [`input::Reader`](input/struct.Reader.html),
+//! settings and profiles with [`config::ConfigFile`](config/struct.ConfigFile.html).
+#![allow(unused_features)]
+#![feature(test)]
+#[cfg(test)]
+extern crate test;
+
+mod rand;
+pub mod placement;
+pub mod bounding_box;
+pub mod size;
+pub mod word_placements;
+mod grid;
+mod grid_cell;
+mod letter_index;
+pub mod crossword;
+mod filter;
+pub mod seen;
+pub mod score;
+pub mod budget;
+pub mod observe;
+pub mod config;
+pub mod anchor;
+pub mod generate;
+pub mod strategy;
+pub mod optimize;
+pub mod parallel;
+pub mod search;
+pub mod checkpoint;
+pub mod word_list;
+pub mod normalize;
+pub mod input;
+pub mod error;
+#[cfg(test)]
+mod bench;
+
+pub use placement::{Position, Direction};
+pub use crossword::Crossword;
+pub use generate::Generator;
+pub use parallel::ParIter;
+pub use search::Search;
+pub use checkpoint::Checkpoint;
+pub use config::{Config, ConfigFile};
+pub use anchor::Anchor;
+pub use size::{SizeLimit, Orientation};
+pub use score::{Score, Metric, Weighted};
+pub use strategy::Strategy;
+pub use optimize::Optimizer;
+pub use budget::{Budget, StopReason, Stats};
+pub use observe::{CancelToken, Observer};
+pub use word_placements::Dedup;
+pub use seen::SeenSet;
+pub use word_list::Entry;
+pub use error::CrosswordError;
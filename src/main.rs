@@ -1,58 +1,535 @@
-#![feature(conservative_impl_trait)]
-#![allow(unused_features)]
-#![feature(test)]
-#[cfg(test)]
-extern crate test;
-
-mod rand;
-mod placement;
-mod bounding_box;
-mod word_placements;
-mod grid;
-mod grid_cell;
-mod crossword;
-mod filter;
-mod generate;
-#[cfg(test)]
-mod bench;
+extern crate crossword;
+
+mod cli;
 
 use std::io;
 use std::io::prelude::*;
 use std::env;
+use std::path::Path;
+use std::process;
+use std::sync::Arc;
+use std::sync::mpsc::{channel, Sender, RecvTimeoutError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crossword::{Generator, ParIter, Search, Checkpoint, Crossword, CrosswordError, Entry, StopReason, ConfigFile, Strategy};
+use crossword::search::Step;
+use crossword::config::DEFAULT_CONFIG_PATH;
+use crossword::placement::parse_positions;
+use crossword::word_list::WordListError;
+use crossword::input::{Reader, InputOptions};
+use crossword::normalize::Normalizer;
+use cli::{Cli, Command, Options};
 
-use generate::Generator;
+// exit codes, also listed in cli::USAGE
+const EXIT_NO_CROSSWORD: i32 = 1;
+const EXIT_INVALID_INPUT: i32 = 2;
+const EXIT_BUDGET_EXHAUSTED: i32 = 3;
 
 fn main() {
-    let flags = vec!["-n", "-s", "-t"];
-    let mut arg_vals = vec![None; flags.len()];
-    let args: Vec<_> = env::args().collect();
-    for pair in args.windows(2) {
-        let (option, arg) = (pair[0].clone(), pair[1].clone());
-        for i in 0..arg_vals.len() {
-            if &*option == flags[i] {
-                match arg.parse::<u64>() {
-                    Ok(n) => { arg_vals[i] = Some(n) },
-                    Err(e) => {
-                        println!("-s {}: {}", arg, e);
-                        return
-                    }
-                }
+    process::exit(run());
+}
+
+// crosswords and reports go to stdout as YAML, diagnostics go to stderr
+fn run() -> i32 {
+    let args: Vec<_> = env::args().skip(1).collect();
+    let Cli { command, options } = match cli::parse(&args) {
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            eprintln!();
+            eprint!("{}", cli::USAGE);
+            return EXIT_INVALID_INPUT
+        }
+    };
+    if command == Command::Help {
+        print!("{}", cli::USAGE);
+        return 0
+    }
+    let options = match apply_config_file(&args, options) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return EXIT_INVALID_INPUT
+        }
+    };
+
+    let mut reader = Reader::new(InputOptions {
+        fold_case: options.fold_case,
+        lossy: options.lossy
+    });
+    if options.batch {
+        let batch = read_batch(&mut reader, &options);
+        for notice in reader.notices() {
+            eprintln!("warning: {}", notice);
+        }
+        return match batch {
+            Ok(batch) => run_batch(command, &batch, &options),
+            Err(e) => {
+                eprintln!("error: {}", e);
+                EXIT_INVALID_INPUT
             }
         }
     }
-    let num_areas = arg_vals[0].unwrap_or(0) as usize;
-    let seed = arg_vals[1].unwrap_or(0) as u64;
-    let num_iters = arg_vals[2].unwrap_or(1) as usize;
+    let entries = read_entries(&mut reader, &options);
+    for notice in reader.notices() {
+        eprintln!("warning: {}", notice);
+    }
+    let entries = match entries {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return EXIT_INVALID_INPUT
+        }
+    };
+    let result = match command {
+        Command::Generate => generate(&entries, &options).map(|(num_crosswords, stop_reason)| {
+            exit_code(num_crosswords, stop_reason)
+        }),
+        Command::Stats => stats(&entries, &options).map(|(num_crosswords, stop_reason)| {
+            exit_code(num_crosswords, stop_reason)
+        }),
+        Command::Optimize => optimize(&entries, &options).map(|is_complete| {
+            if is_complete { 0 } else { EXIT_NO_CROSSWORD }
+        }),
+        Command::Render | Command::Validate => check_layout(command, &entries, &options),
+        Command::Help => unreachable!()
+    };
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            EXIT_INVALID_INPUT
+        }
+    }
+}
+
+// render or validate the layout given by --placements
+fn check_layout(command: Command, entries: &[Entry], options: &Options) -> Result<i32, CrosswordError> {
+    let positions = parse_positions(options.placements.as_ref().unwrap())?;
+    let words: Vec<_> = entries.iter().map(|entry| &entry.word).collect();
+    if command == Command::Render {
+        println!("{}", Crossword::from_positions(&words, &positions)?.oriented(options.config.size.orientation));
+        return Ok(0)
+    }
+    match Crossword::validate(&words, &positions) {
+        Ok(_) => {
+            println!("valid: true");
+            Ok(0)
+        },
+        Err(e @ CrosswordError::Collision { .. }) | Err(e @ CrosswordError::Disconnected { .. }) => {
+            println!("valid: false");
+            eprintln!("error: {}", e);
+            Ok(EXIT_NO_CROSSWORD)
+        },
+        Err(e) => Err(e)
+    }
+}
+
+// settings come from the defaults, then the config file and profile, then the command line
+fn apply_config_file(args: &[String], options: Options) -> Result<Options, String> {
+    let config_path = match options.config_path {
+        Some(ref path) => Some(path.clone()),
+        None if Path::new(DEFAULT_CONFIG_PATH).exists() => Some(DEFAULT_CONFIG_PATH.to_string()),
+        None => None
+    };
+    let config_path = match config_path {
+        Some(path) => path,
+        None => match options.profile {
+            Some(ref profile) => {
+                return Err(format!("no config file for profile '{}' (use --config or create {})", profile, DEFAULT_CONFIG_PATH))
+            },
+            None => return Ok(options)
+        }
+    };
+    let config_file = ConfigFile::load(&config_path).map_err(|e| e.to_string())?;
+    let mut base = Options::default();
+    config_file.apply(&mut base.config, options.profile.as_ref().map(|profile| &profile[..])).map_err(|e| e.to_string())?;
+    cli::parse_onto(args, base).map(|cli| cli.options).map_err(|e| e.to_string())
+}
+
+// a search cut short by a budget didn't show that no crossword is possible,
+// even when it found none. stopping at --max-results is a success.
+fn exit_code(num_crosswords: usize, stop_reason: StopReason) -> i32 {
+    match stop_reason {
+        StopReason::Timeout | StopReason::MaxNodes | StopReason::Cancelled => EXIT_BUDGET_EXHAUSTED,
+        _ if num_crosswords == 0 => EXIT_NO_CROSSWORD,
+        _ => 0
+    }
+}
 
+// words come from the --words files if any, otherwise from stdin up to the first blank line
+fn read_entries(reader: &mut Reader, options: &Options) -> Result<Vec<Entry>, WordListError> {
+    if !options.word_files.is_empty() {
+        let mut entries = vec![];
+        for path in &options.word_files {
+            entries.extend(reader.load(path)?);
+        }
+        return Ok(entries)
+    }
     let stdin = io::stdin();
-    let words = stdin.lock().lines()
-        .map(|line| line.unwrap())
-        .take_while(|line| line.len() > 0)
-        .collect::<Vec<_>>();
-    let words = words.iter().map(|s| s).collect();
-    let gen = Generator::new(words, num_areas, seed);
+    let mut stdin = stdin.lock();
+    let mut entries = vec![];
+    let mut line = vec![];
+    for line_number in 1.. {
+        line.clear();
+        let num_bytes = stdin.read_until(b'\n', &mut line).map_err(|e| WordListError::Io {
+            source: "<stdin>".to_string(),
+            reason: e.to_string()
+        })?;
+        if num_bytes == 0 || line.iter().all(|b| b" \t\r\n".contains(b)) {
+            break
+        }
+        if let Some(entry) = reader.read_line("<stdin>", line_number, &line)? {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+// one word list per --words file, or per blank-line-separated block of stdin
+fn read_batch(reader: &mut Reader, options: &Options) -> Result<Vec<(String, Vec<Entry>)>, WordListError> {
+    if !options.word_files.is_empty() {
+        return options.word_files.iter()
+            .map(|path| {
+                reader.reset();
+                reader.load(path).map(|entries| (path.clone(), entries))
+            })
+            .collect()
+    }
+    let mut bytes = vec![];
+    io::stdin().read_to_end(&mut bytes).map_err(|e| WordListError::Io {
+        source: "<stdin>".to_string(),
+        reason: e.to_string()
+    })?;
+    let batch = reader.read_batch("<stdin>", &bytes)?;
+    Ok(batch.into_iter().map(|entries| ("<stdin>".to_string(), entries)).collect())
+}
+
+// each word list is its own YAML document, followed by a summary document.
+// the exit code is the worst of the puzzles' exit codes: invalid input, then no
+// crossword, then budget exhausted.
+fn run_batch(command: Command, batch: &[(String, Vec<Entry>)], options: &Options) -> i32 {
+    let mut summary = Summary::default();
+    for (i, (source, entries)) in batch.iter().enumerate() {
+        println!("---");
+        println!("puzzle: {}", i + 1);
+        println!("source: {}", source);
+        let result = if command == Command::Stats {
+            stats(entries, options)
+        } else {
+            generate(entries, options)
+        };
+        summary.add(i, match result {
+            Ok(result) => Some(result),
+            Err(e) => {
+                eprintln!("error: puzzle {} ({}): {}", i + 1, source, e);
+                None
+            }
+        });
+    }
+    let sources: Vec<&str> = batch.iter().map(|(source, _)| &source[..]).collect();
+    print!("{}", summary.to_yaml(&sources));
+    summary.exit_code()
+}
+
+// the puzzles of a batch by how they went. a puzzle that ran out of budget is
+// solved if it found a crossword before it did.
+#[derive(Default)]
+struct Summary {
+    num_puzzles: usize,
+    solved: Vec<usize>,
+    invalid: Vec<usize>,
+    unsolved: Vec<usize>,
+    incomplete: Vec<usize>
+}
+impl Summary {
+    // the number of crosswords puzzle i found and why it stopped, or None if its
+    // input was invalid
+    fn add(&mut self, i: usize, result: Option<(usize, StopReason)>) {
+        self.num_puzzles += 1;
+        let (num_crosswords, stop_reason) = match result {
+            Some(result) => result,
+            None => {
+                self.invalid.push(i);
+                return
+            }
+        };
+        if num_crosswords > 0 {
+            self.solved.push(i);
+        }
+        match exit_code(num_crosswords, stop_reason) {
+            EXIT_NO_CROSSWORD => self.unsolved.push(i),
+            EXIT_BUDGET_EXHAUSTED => self.incomplete.push(i),
+            _ => {}
+        }
+    }
+    fn exit_code(&self) -> i32 {
+        if !self.invalid.is_empty() {
+            EXIT_INVALID_INPUT
+        } else if !self.unsolved.is_empty() {
+            EXIT_NO_CROSSWORD
+        } else if !self.incomplete.is_empty() {
+            EXIT_BUDGET_EXHAUSTED
+        } else {
+            0
+        }
+    }
+    // the summary document, with each puzzle's source
+    fn to_yaml(&self, sources: &[&str]) -> String {
+        let mut yaml = String::new();
+        yaml.push_str("---\nsummary:\n");
+        yaml.push_str(&format!("  puzzles: {}\n", self.num_puzzles));
+        yaml.push_str(&format!("  solved: {}\n", self.solved.len()));
+        for &(key, puzzles) in &[("invalid", &self.invalid), ("unsolved", &self.unsolved), ("incomplete", &self.incomplete)] {
+            yaml.push_str(&format!("  {}:{}\n", key, if puzzles.is_empty() { " []" } else { "" }));
+            for &i in puzzles.iter() {
+                yaml.push_str(&format!("    - {{ puzzle: {}, source: {} }}\n", i + 1, sources[i]));
+            }
+        }
+        yaml
+    }
+}
+
+// locked words are normalized like the word list, so they match its words
+fn make_generator(entries: &[Entry], options: &Options) -> Result<Arc<Generator>, CrosswordError> {
+    let normalizer = Normalizer::new(options.fold_case);
+    let locks: Vec<_> = options.locks.iter().map(|&(ref word, pos)| (normalizer.normalize(word), pos)).collect();
+    Generator::from_entries(entries, options.config.clone())?.with_locked(&locks).map(Arc::new)
+}
+
+fn crosswords<'a>(gen: &'a Arc<Generator>, options: &Options) -> Result<Box<dyn Iterator<Item=Crossword> + 'a>, CrosswordError> {
+    if let Some(ref path) = options.resume {
+        let search = gen.resume(&Checkpoint::load(path)?)?;
+        return Ok(Box::new(Checkpointed::new(search, options)))
+    }
+    if options.checkpoint.is_some() {
+        return Ok(Box::new(Checkpointed::new(gen.search(), options)))
+    }
+    if options.config.strategy != Strategy::Dfs {
+        Ok(gen.iter())
+    } else if options.config.num_threads > 1 {
+        Ok(Box::new(ParIter::new(gen.clone(), options.config.num_threads)))
+    } else {
+        Ok(gen.multi_iter(options.config.num_iters))
+    }
+}
+
+// reading the clock on every step of the search is measurable, so only every so often
+const CHECKPOINT_CLOCK_INTERVAL: usize = 4096;
+
+// saves the search to --checkpoint every --checkpoint-interval, and once it stops.
+// saves happen before a step, so every crossword found before a save has been printed.
+struct Checkpointed<'a> {
+    search: Search<'a>,
+    path: Option<String>,
+    interval: Duration,
+    last_save: Instant,
+    num_steps: usize
+}
+impl<'a> Checkpointed<'a> {
+    fn new(search: Search<'a>, options: &Options) -> Checkpointed<'a> {
+        Checkpointed {
+            search,
+            path: options.checkpoint.clone(),
+            interval: options.checkpoint_interval,
+            last_save: Instant::now(),
+            num_steps: 0
+        }
+    }
+    fn save(&mut self) {
+        if let Some(ref path) = self.path {
+            if let Err(e) = self.search.checkpoint().and_then(|checkpoint| checkpoint.save(path)) {
+                eprintln!("warning: couldn't save checkpoint: {}", e);
+            }
+        }
+        self.last_save = Instant::now();
+    }
+}
+impl<'a> Iterator for Checkpointed<'a> {
+    type Item = Crossword;
+    fn next(&mut self) -> Option<Crossword> {
+        loop {
+            self.num_steps += 1;
+            if self.num_steps.is_multiple_of(CHECKPOINT_CLOCK_INTERVAL) && self.last_save.elapsed() >= self.interval {
+                self.save();
+            }
+            match self.search.step() {
+                Step::Found(crossword) => return Some(crossword),
+                Step::Searching => {},
+                Step::Done => {
+                    self.save();
+                    return None
+                }
+            }
+        }
+    }
+}
+
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+// prints the generator's stats to stderr every PROGRESS_INTERVAL, from a thread of its
+// own so a slow step doesn't hold it up, and once more when dropped
+struct Progress {
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>
+}
+impl Progress {
+    fn start(gen: &Arc<Generator>, options: &Options) -> Progress {
+        if !options.progress {
+            return Progress { stop: None, thread: None }
+        }
+        let (stop, stopped) = channel();
+        let gen = gen.clone();
+        let thread = thread::spawn(move || {
+            loop {
+                let is_stopped = stopped.recv_timeout(PROGRESS_INTERVAL) != Err(RecvTimeoutError::Timeout);
+                eprintln!("progress: {}", gen.stats());
+                if is_stopped {
+                    return
+                }
+            }
+        });
+        Progress { stop: Some(stop), thread: Some(thread) }
+    }
+}
+impl Drop for Progress {
+    fn drop(&mut self) {
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            thread.join().ok();
+        }
+    }
+}
+
+fn generate(entries: &[Entry], options: &Options) -> Result<(usize, StopReason), CrosswordError> {
+    let gen = make_generator(entries, options)?;
+    let crosswords = crosswords(&gen, options)?;
+    let _progress = Progress::start(&gen, options);
+    println!("{}", gen);
+    let mut count = 0;
+    for crossword in crosswords {
+        count += 1;
+        print!("{}", crossword.oriented(options.config.size.orientation));
+        if let Some(score) = gen.score(&crossword) {
+            println!("  score: {}", score);
+        }
+        if options.config.partial {
+            let left_out: Vec<_> = gen.left_out(&crossword).collect();
+            println!("  left_out: [{}]", left_out.join(", "));
+        }
+        if !gen.locked().is_empty() {
+            let locked: Vec<_> = gen.locked().iter().map(|&(word_index, _)| gen.word(word_index)).collect();
+            println!("  locked: [{}]", locked.join(", "));
+        }
+        if entries.iter().any(|entry| entry.clue.is_some()) {
+            println!("  clues:");
+            for (pos, word, clue) in gen.clues_for(&crossword) {
+                println!("    - {{ position: [{}], word: {}, clue: {:?} }}", pos, word, clue.unwrap_or(""));
+            }
+        }
+        println!();
+    }
+    println!("status: {}", gen.stop_reason());
+    Ok((count, gen.stop_reason()))
+}
+
+fn stats(entries: &[Entry], options: &Options) -> Result<(usize, StopReason), CrosswordError> {
+    let gen = make_generator(entries, options)?;
+    let crosswords = crosswords(&gen, options)?;
+    let progress = Progress::start(&gen, options);
     println!("{}", gen);
-    for crossword in gen.multi_iter(num_iters) {
-        println!("{}", crossword);
+    let (mut count, mut min_area, mut max_overlaps, mut min_score) = (0, None, None, None);
+    for crossword in crosswords {
+        let area = crossword.bounding_box().area();
+        let overlaps = crossword.num_overlaps();
+        count += 1;
+        min_area = Some(min_area.map_or(area, |min_area: i16| min_area.min(area)));
+        max_overlaps = Some(max_overlaps.map_or(overlaps, |max_overlaps: i8| max_overlaps.max(overlaps)));
+        if let Some(score) = gen.score(&crossword) {
+            min_score = Some(min_score.map_or(score, |min_score: f64| min_score.min(score)));
+        }
+    }
+    println!("status: {}", gen.stop_reason());
+    println!("crosswords: {}", count);
+    if let (Some(min_area), Some(max_overlaps)) = (min_area, max_overlaps) {
+        println!("min_area: {}", min_area);
+        println!("max_overlaps: {}", max_overlaps);
+    }
+    if let Some(min_score) = min_score {
+        println!("min_score: {}", min_score);
+    }
+    drop(progress);
+    let run = gen.stats();
+    println!("nodes: {}", run.nodes);
+    println!("rejected:");
+    println!("  collision: {}", run.collisions);
+    println!("  area: {}", run.area_rejections);
+    println!("  seen: {}", run.seen_rejections);
+    println!("seen_set:");
+    println!("  entries: {}", run.seen.entries);
+    println!("  bytes: {}", run.seen.bytes);
+    println!("  not_remembered: {}", run.seen.dropped);
+    println!("  false_positive_rate: {}", run.seen.false_positive_rate);
+    println!("elapsed: {:.3}", run.elapsed.as_secs_f64());
+    Ok((count, gen.stop_reason()))
+}
+
+// prints each better layout as it's found. true if the best places every word.
+fn optimize(entries: &[Entry], options: &Options) -> Result<bool, CrosswordError> {
+    let gen = make_generator(entries, options)?;
+    let start = match options.placements {
+        Some(ref placements) => {
+            let words: Vec<_> = entries.iter().map(|entry| &entry.word).collect();
+            Crossword::validate(&words, &parse_positions(placements)?)?
+        },
+        None => gen.greedy()
+    };
+    let mut optimizer = gen.optimize(start)?;
+    let _progress = Progress::start(&gen, options);
+    println!("{}", gen);
+    let mut is_complete = false;
+    while let Some(crossword) = optimizer.next() {
+        print!("{}", crossword.oriented(options.config.size.orientation));
+        println!("  move: {}", optimizer.num_moves());
+        if let Some(score) = gen.score(&crossword) {
+            println!("  score: {}", score);
+        }
+        let left_out: Vec<_> = gen.left_out(&crossword).collect();
+        if !left_out.is_empty() {
+            println!("  left_out: [{}]", left_out.join(", "));
+        }
+        is_complete = left_out.is_empty();
+        println!();
+    }
+    println!("status: {}", gen.stop_reason());
+    println!("moves: {}", optimizer.num_moves());
+    Ok(is_complete)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary() {
+        let mut summary = Summary::default();
+        summary.add(0, Some((2, StopReason::Exhausted)));
+        // out of budget, before and after finding a crossword
+        summary.add(1, Some((0, StopReason::MaxNodes)));
+        summary.add(2, Some((1, StopReason::Timeout)));
+        summary.add(3, Some((0, StopReason::Exhausted)));
+        let yaml = summary.to_yaml(&["a", "b", "c", "d"]);
+        assert!(yaml.contains("  puzzles: 4\n  solved: 2\n"), "{}", yaml);
+        assert!(yaml.contains("  unsolved:\n    - { puzzle: 4, source: d }\n"), "{}", yaml);
+        assert!(yaml.contains("  incomplete:\n    - { puzzle: 2, source: b }\n    - { puzzle: 3, source: c }\n"), "{}", yaml);
+        assert_eq!(EXIT_NO_CROSSWORD, summary.exit_code());
+
+        let mut summary = Summary::default();
+        summary.add(0, Some((0, StopReason::MaxNodes)));
+        assert!(summary.to_yaml(&["a"]).contains("  solved: 0\n  invalid: []\n  unsolved: []\n"));
+        assert_eq!(EXIT_BUDGET_EXHAUSTED, summary.exit_code());
+        summary.add(1, None);
+        assert_eq!(EXIT_INVALID_INPUT, summary.exit_code());
     }
 }
@@ -0,0 +1,309 @@
+//! Putting words in one form, so they compare equal however they were typed.
+
+use std::cmp::Ordering;
+
+/// Canonical composition of a base letter and its combining marks, so that a
+/// word typed with combining marks matches the same word typed with precomposed
+/// letters ("e" + U+0301 == "é"). The marks are put in canonical order first, so
+/// stacked marks match however they were typed ("e" + U+0302 + U+0323 == "e" +
+/// U+0323 + U+0302 == "ệ"). Covers the Latin blocks, which is where word lists mix
+/// the two forms in practice; anything else passes through unchanged.
+pub fn compose(s: &str) -> String {
+    let mut chars: Vec<char> = s.chars().collect();
+    reorder(&mut chars);
+    let mut out: Vec<char> = Vec::with_capacity(chars.len());
+    // the last starter, and the class of the last mark after it that didn't compose
+    let mut starter = None;
+    let mut last_class = 0;
+    for c in chars {
+        let class = combining_class(c);
+        if let Some(i) = starter {
+            // the marks in between are in order, so only the last can block it
+            let is_blocked = out.len() > i + 1 && (last_class == 0 || last_class >= class);
+            if let (false, Some(composed)) = (is_blocked, compose_pair(out[i], c)) {
+                out[i] = composed;
+                continue
+            }
+        }
+        if class == 0 {
+            starter = Some(out.len());
+        }
+        last_class = class;
+        out.push(c);
+    }
+    out.into_iter().collect()
+}
+
+// sorts each run of marks by combining class, keeping the order of marks of the same class
+fn reorder(chars: &mut [char]) {
+    for marks in chars.split_mut(|&c| combining_class(c) == 0) {
+        marks.sort_by_key(|&c| combining_class(c));
+    }
+}
+
+// 0 for a starter. Marks outside the table are taken as starters, so they're never
+// reordered and nothing composes across them.
+fn combining_class(c: char) -> u8 {
+    COMBINING_CLASSES.binary_search_by(|&(first, last, _)| {
+        if last < c { Ordering::Less } else if first > c { Ordering::Greater } else { Ordering::Equal }
+    }).ok().map_or(0, |i| COMBINING_CLASSES[i].2)
+}
+
+fn compose_pair(base: char, mark: char) -> Option<char> {
+    COMPOSITIONS.binary_search_by_key(&(base, mark), |&(base, mark, _)| (base, mark))
+        .ok()
+        .map(|i| COMPOSITIONS[i].2)
+}
+
+/// Puts words in one form, so the same word typed two ways is read as the same word.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct Normalizer {
+    /// Lowercase words, after composing them.
+    pub fold_case: bool
+}
+impl Normalizer {
+    /// A normalizer that lowercases words if `fold_case` is set.
+    pub fn new(fold_case: bool) -> Normalizer {
+        Normalizer {
+            fold_case
+        }
+    }
+    /// The word trimmed and composed, and lowercased if `fold_case` is set.
+    pub fn normalize(&self, word: &str) -> String {
+        let word = compose(word.trim());
+        if self.fold_case {
+            word.to_lowercase()
+        } else {
+            word
+        }
+    }
+}
+
+// (first, last, canonical combining class) of the Combining Diacritical Marks
+// block, U+0300 to U+036F, from UnicodeData.txt in Unicode 14.0
+static COMBINING_CLASSES: &[(char, char, u8)] = &[
+    ('\u{300}', '\u{314}', 230), ('\u{315}', '\u{315}', 232), ('\u{316}', '\u{319}', 220), ('\u{31a}', '\u{31a}', 232),
+    ('\u{31b}', '\u{31b}', 216), ('\u{31c}', '\u{320}', 220), ('\u{321}', '\u{322}', 202), ('\u{323}', '\u{326}', 220),
+    ('\u{327}', '\u{328}', 202), ('\u{329}', '\u{333}', 220), ('\u{334}', '\u{338}', 1), ('\u{339}', '\u{33c}', 220),
+    ('\u{33d}', '\u{344}', 230), ('\u{345}', '\u{345}', 240), ('\u{346}', '\u{346}', 230), ('\u{347}', '\u{349}', 220),
+    ('\u{34a}', '\u{34c}', 230), ('\u{34d}', '\u{34e}', 220), ('\u{34f}', '\u{34f}', 0), ('\u{350}', '\u{352}', 230),
+    ('\u{353}', '\u{356}', 220), ('\u{357}', '\u{357}', 230), ('\u{358}', '\u{358}', 232), ('\u{359}', '\u{35a}', 220),
+    ('\u{35b}', '\u{35b}', 230), ('\u{35c}', '\u{35c}', 233), ('\u{35d}', '\u{35e}', 234), ('\u{35f}', '\u{35f}', 233),
+    ('\u{360}', '\u{361}', 234), ('\u{362}', '\u{362}', 233), ('\u{363}', '\u{36f}', 230),
+];
+
+// (base, combining mark, composed), sorted by (base, mark): every canonical
+// decomposition into two characters in UnicodeData.txt, Unicode 14.0, that isn't
+// excluded from composition and composes to a letter in Latin-1 Supplement to
+// Latin Extended-B (U+00C0 to U+024F) or Latin Extended Additional (U+1E00 to
+// U+1EFF). Compositions never change between versions, so it won't go stale.
+static COMPOSITIONS: &[(char, char, char)] = &[
+    ('\u{41}', '\u{300}', '\u{c0}'), ('\u{41}', '\u{301}', '\u{c1}'), ('\u{41}', '\u{302}', '\u{c2}'),
+    ('\u{41}', '\u{303}', '\u{c3}'), ('\u{41}', '\u{304}', '\u{100}'), ('\u{41}', '\u{306}', '\u{102}'),
+    ('\u{41}', '\u{307}', '\u{226}'), ('\u{41}', '\u{308}', '\u{c4}'), ('\u{41}', '\u{309}', '\u{1ea2}'),
+    ('\u{41}', '\u{30a}', '\u{c5}'), ('\u{41}', '\u{30c}', '\u{1cd}'), ('\u{41}', '\u{30f}', '\u{200}'),
+    ('\u{41}', '\u{311}', '\u{202}'), ('\u{41}', '\u{323}', '\u{1ea0}'), ('\u{41}', '\u{325}', '\u{1e00}'),
+    ('\u{41}', '\u{328}', '\u{104}'), ('\u{42}', '\u{307}', '\u{1e02}'), ('\u{42}', '\u{323}', '\u{1e04}'),
+    ('\u{42}', '\u{331}', '\u{1e06}'), ('\u{43}', '\u{301}', '\u{106}'), ('\u{43}', '\u{302}', '\u{108}'),
+    ('\u{43}', '\u{307}', '\u{10a}'), ('\u{43}', '\u{30c}', '\u{10c}'), ('\u{43}', '\u{327}', '\u{c7}'),
+    ('\u{44}', '\u{307}', '\u{1e0a}'), ('\u{44}', '\u{30c}', '\u{10e}'), ('\u{44}', '\u{323}', '\u{1e0c}'),
+    ('\u{44}', '\u{327}', '\u{1e10}'), ('\u{44}', '\u{32d}', '\u{1e12}'), ('\u{44}', '\u{331}', '\u{1e0e}'),
+    ('\u{45}', '\u{300}', '\u{c8}'), ('\u{45}', '\u{301}', '\u{c9}'), ('\u{45}', '\u{302}', '\u{ca}'),
+    ('\u{45}', '\u{303}', '\u{1ebc}'), ('\u{45}', '\u{304}', '\u{112}'), ('\u{45}', '\u{306}', '\u{114}'),
+    ('\u{45}', '\u{307}', '\u{116}'), ('\u{45}', '\u{308}', '\u{cb}'), ('\u{45}', '\u{309}', '\u{1eba}'),
+    ('\u{45}', '\u{30c}', '\u{11a}'), ('\u{45}', '\u{30f}', '\u{204}'), ('\u{45}', '\u{311}', '\u{206}'),
+    ('\u{45}', '\u{323}', '\u{1eb8}'), ('\u{45}', '\u{327}', '\u{228}'), ('\u{45}', '\u{328}', '\u{118}'),
+    ('\u{45}', '\u{32d}', '\u{1e18}'), ('\u{45}', '\u{330}', '\u{1e1a}'), ('\u{46}', '\u{307}', '\u{1e1e}'),
+    ('\u{47}', '\u{301}', '\u{1f4}'), ('\u{47}', '\u{302}', '\u{11c}'), ('\u{47}', '\u{304}', '\u{1e20}'),
+    ('\u{47}', '\u{306}', '\u{11e}'), ('\u{47}', '\u{307}', '\u{120}'), ('\u{47}', '\u{30c}', '\u{1e6}'),
+    ('\u{47}', '\u{327}', '\u{122}'), ('\u{48}', '\u{302}', '\u{124}'), ('\u{48}', '\u{307}', '\u{1e22}'),
+    ('\u{48}', '\u{308}', '\u{1e26}'), ('\u{48}', '\u{30c}', '\u{21e}'), ('\u{48}', '\u{323}', '\u{1e24}'),
+    ('\u{48}', '\u{327}', '\u{1e28}'), ('\u{48}', '\u{32e}', '\u{1e2a}'), ('\u{49}', '\u{300}', '\u{cc}'),
+    ('\u{49}', '\u{301}', '\u{cd}'), ('\u{49}', '\u{302}', '\u{ce}'), ('\u{49}', '\u{303}', '\u{128}'),
+    ('\u{49}', '\u{304}', '\u{12a}'), ('\u{49}', '\u{306}', '\u{12c}'), ('\u{49}', '\u{307}', '\u{130}'),
+    ('\u{49}', '\u{308}', '\u{cf}'), ('\u{49}', '\u{309}', '\u{1ec8}'), ('\u{49}', '\u{30c}', '\u{1cf}'),
+    ('\u{49}', '\u{30f}', '\u{208}'), ('\u{49}', '\u{311}', '\u{20a}'), ('\u{49}', '\u{323}', '\u{1eca}'),
+    ('\u{49}', '\u{328}', '\u{12e}'), ('\u{49}', '\u{330}', '\u{1e2c}'), ('\u{4a}', '\u{302}', '\u{134}'),
+    ('\u{4b}', '\u{301}', '\u{1e30}'), ('\u{4b}', '\u{30c}', '\u{1e8}'), ('\u{4b}', '\u{323}', '\u{1e32}'),
+    ('\u{4b}', '\u{327}', '\u{136}'), ('\u{4b}', '\u{331}', '\u{1e34}'), ('\u{4c}', '\u{301}', '\u{139}'),
+    ('\u{4c}', '\u{30c}', '\u{13d}'), ('\u{4c}', '\u{323}', '\u{1e36}'), ('\u{4c}', '\u{327}', '\u{13b}'),
+    ('\u{4c}', '\u{32d}', '\u{1e3c}'), ('\u{4c}', '\u{331}', '\u{1e3a}'), ('\u{4d}', '\u{301}', '\u{1e3e}'),
+    ('\u{4d}', '\u{307}', '\u{1e40}'), ('\u{4d}', '\u{323}', '\u{1e42}'), ('\u{4e}', '\u{300}', '\u{1f8}'),
+    ('\u{4e}', '\u{301}', '\u{143}'), ('\u{4e}', '\u{303}', '\u{d1}'), ('\u{4e}', '\u{307}', '\u{1e44}'),
+    ('\u{4e}', '\u{30c}', '\u{147}'), ('\u{4e}', '\u{323}', '\u{1e46}'), ('\u{4e}', '\u{327}', '\u{145}'),
+    ('\u{4e}', '\u{32d}', '\u{1e4a}'), ('\u{4e}', '\u{331}', '\u{1e48}'), ('\u{4f}', '\u{300}', '\u{d2}'),
+    ('\u{4f}', '\u{301}', '\u{d3}'), ('\u{4f}', '\u{302}', '\u{d4}'), ('\u{4f}', '\u{303}', '\u{d5}'),
+    ('\u{4f}', '\u{304}', '\u{14c}'), ('\u{4f}', '\u{306}', '\u{14e}'), ('\u{4f}', '\u{307}', '\u{22e}'),
+    ('\u{4f}', '\u{308}', '\u{d6}'), ('\u{4f}', '\u{309}', '\u{1ece}'), ('\u{4f}', '\u{30b}', '\u{150}'),
+    ('\u{4f}', '\u{30c}', '\u{1d1}'), ('\u{4f}', '\u{30f}', '\u{20c}'), ('\u{4f}', '\u{311}', '\u{20e}'),
+    ('\u{4f}', '\u{31b}', '\u{1a0}'), ('\u{4f}', '\u{323}', '\u{1ecc}'), ('\u{4f}', '\u{328}', '\u{1ea}'),
+    ('\u{50}', '\u{301}', '\u{1e54}'), ('\u{50}', '\u{307}', '\u{1e56}'), ('\u{52}', '\u{301}', '\u{154}'),
+    ('\u{52}', '\u{307}', '\u{1e58}'), ('\u{52}', '\u{30c}', '\u{158}'), ('\u{52}', '\u{30f}', '\u{210}'),
+    ('\u{52}', '\u{311}', '\u{212}'), ('\u{52}', '\u{323}', '\u{1e5a}'), ('\u{52}', '\u{327}', '\u{156}'),
+    ('\u{52}', '\u{331}', '\u{1e5e}'), ('\u{53}', '\u{301}', '\u{15a}'), ('\u{53}', '\u{302}', '\u{15c}'),
+    ('\u{53}', '\u{307}', '\u{1e60}'), ('\u{53}', '\u{30c}', '\u{160}'), ('\u{53}', '\u{323}', '\u{1e62}'),
+    ('\u{53}', '\u{326}', '\u{218}'), ('\u{53}', '\u{327}', '\u{15e}'), ('\u{54}', '\u{307}', '\u{1e6a}'),
+    ('\u{54}', '\u{30c}', '\u{164}'), ('\u{54}', '\u{323}', '\u{1e6c}'), ('\u{54}', '\u{326}', '\u{21a}'),
+    ('\u{54}', '\u{327}', '\u{162}'), ('\u{54}', '\u{32d}', '\u{1e70}'), ('\u{54}', '\u{331}', '\u{1e6e}'),
+    ('\u{55}', '\u{300}', '\u{d9}'), ('\u{55}', '\u{301}', '\u{da}'), ('\u{55}', '\u{302}', '\u{db}'),
+    ('\u{55}', '\u{303}', '\u{168}'), ('\u{55}', '\u{304}', '\u{16a}'), ('\u{55}', '\u{306}', '\u{16c}'),
+    ('\u{55}', '\u{308}', '\u{dc}'), ('\u{55}', '\u{309}', '\u{1ee6}'), ('\u{55}', '\u{30a}', '\u{16e}'),
+    ('\u{55}', '\u{30b}', '\u{170}'), ('\u{55}', '\u{30c}', '\u{1d3}'), ('\u{55}', '\u{30f}', '\u{214}'),
+    ('\u{55}', '\u{311}', '\u{216}'), ('\u{55}', '\u{31b}', '\u{1af}'), ('\u{55}', '\u{323}', '\u{1ee4}'),
+    ('\u{55}', '\u{324}', '\u{1e72}'), ('\u{55}', '\u{328}', '\u{172}'), ('\u{55}', '\u{32d}', '\u{1e76}'),
+    ('\u{55}', '\u{330}', '\u{1e74}'), ('\u{56}', '\u{303}', '\u{1e7c}'), ('\u{56}', '\u{323}', '\u{1e7e}'),
+    ('\u{57}', '\u{300}', '\u{1e80}'), ('\u{57}', '\u{301}', '\u{1e82}'), ('\u{57}', '\u{302}', '\u{174}'),
+    ('\u{57}', '\u{307}', '\u{1e86}'), ('\u{57}', '\u{308}', '\u{1e84}'), ('\u{57}', '\u{323}', '\u{1e88}'),
+    ('\u{58}', '\u{307}', '\u{1e8a}'), ('\u{58}', '\u{308}', '\u{1e8c}'), ('\u{59}', '\u{300}', '\u{1ef2}'),
+    ('\u{59}', '\u{301}', '\u{dd}'), ('\u{59}', '\u{302}', '\u{176}'), ('\u{59}', '\u{303}', '\u{1ef8}'),
+    ('\u{59}', '\u{304}', '\u{232}'), ('\u{59}', '\u{307}', '\u{1e8e}'), ('\u{59}', '\u{308}', '\u{178}'),
+    ('\u{59}', '\u{309}', '\u{1ef6}'), ('\u{59}', '\u{323}', '\u{1ef4}'), ('\u{5a}', '\u{301}', '\u{179}'),
+    ('\u{5a}', '\u{302}', '\u{1e90}'), ('\u{5a}', '\u{307}', '\u{17b}'), ('\u{5a}', '\u{30c}', '\u{17d}'),
+    ('\u{5a}', '\u{323}', '\u{1e92}'), ('\u{5a}', '\u{331}', '\u{1e94}'), ('\u{61}', '\u{300}', '\u{e0}'),
+    ('\u{61}', '\u{301}', '\u{e1}'), ('\u{61}', '\u{302}', '\u{e2}'), ('\u{61}', '\u{303}', '\u{e3}'),
+    ('\u{61}', '\u{304}', '\u{101}'), ('\u{61}', '\u{306}', '\u{103}'), ('\u{61}', '\u{307}', '\u{227}'),
+    ('\u{61}', '\u{308}', '\u{e4}'), ('\u{61}', '\u{309}', '\u{1ea3}'), ('\u{61}', '\u{30a}', '\u{e5}'),
+    ('\u{61}', '\u{30c}', '\u{1ce}'), ('\u{61}', '\u{30f}', '\u{201}'), ('\u{61}', '\u{311}', '\u{203}'),
+    ('\u{61}', '\u{323}', '\u{1ea1}'), ('\u{61}', '\u{325}', '\u{1e01}'), ('\u{61}', '\u{328}', '\u{105}'),
+    ('\u{62}', '\u{307}', '\u{1e03}'), ('\u{62}', '\u{323}', '\u{1e05}'), ('\u{62}', '\u{331}', '\u{1e07}'),
+    ('\u{63}', '\u{301}', '\u{107}'), ('\u{63}', '\u{302}', '\u{109}'), ('\u{63}', '\u{307}', '\u{10b}'),
+    ('\u{63}', '\u{30c}', '\u{10d}'), ('\u{63}', '\u{327}', '\u{e7}'), ('\u{64}', '\u{307}', '\u{1e0b}'),
+    ('\u{64}', '\u{30c}', '\u{10f}'), ('\u{64}', '\u{323}', '\u{1e0d}'), ('\u{64}', '\u{327}', '\u{1e11}'),
+    ('\u{64}', '\u{32d}', '\u{1e13}'), ('\u{64}', '\u{331}', '\u{1e0f}'), ('\u{65}', '\u{300}', '\u{e8}'),
+    ('\u{65}', '\u{301}', '\u{e9}'), ('\u{65}', '\u{302}', '\u{ea}'), ('\u{65}', '\u{303}', '\u{1ebd}'),
+    ('\u{65}', '\u{304}', '\u{113}'), ('\u{65}', '\u{306}', '\u{115}'), ('\u{65}', '\u{307}', '\u{117}'),
+    ('\u{65}', '\u{308}', '\u{eb}'), ('\u{65}', '\u{309}', '\u{1ebb}'), ('\u{65}', '\u{30c}', '\u{11b}'),
+    ('\u{65}', '\u{30f}', '\u{205}'), ('\u{65}', '\u{311}', '\u{207}'), ('\u{65}', '\u{323}', '\u{1eb9}'),
+    ('\u{65}', '\u{327}', '\u{229}'), ('\u{65}', '\u{328}', '\u{119}'), ('\u{65}', '\u{32d}', '\u{1e19}'),
+    ('\u{65}', '\u{330}', '\u{1e1b}'), ('\u{66}', '\u{307}', '\u{1e1f}'), ('\u{67}', '\u{301}', '\u{1f5}'),
+    ('\u{67}', '\u{302}', '\u{11d}'), ('\u{67}', '\u{304}', '\u{1e21}'), ('\u{67}', '\u{306}', '\u{11f}'),
+    ('\u{67}', '\u{307}', '\u{121}'), ('\u{67}', '\u{30c}', '\u{1e7}'), ('\u{67}', '\u{327}', '\u{123}'),
+    ('\u{68}', '\u{302}', '\u{125}'), ('\u{68}', '\u{307}', '\u{1e23}'), ('\u{68}', '\u{308}', '\u{1e27}'),
+    ('\u{68}', '\u{30c}', '\u{21f}'), ('\u{68}', '\u{323}', '\u{1e25}'), ('\u{68}', '\u{327}', '\u{1e29}'),
+    ('\u{68}', '\u{32e}', '\u{1e2b}'), ('\u{68}', '\u{331}', '\u{1e96}'), ('\u{69}', '\u{300}', '\u{ec}'),
+    ('\u{69}', '\u{301}', '\u{ed}'), ('\u{69}', '\u{302}', '\u{ee}'), ('\u{69}', '\u{303}', '\u{129}'),
+    ('\u{69}', '\u{304}', '\u{12b}'), ('\u{69}', '\u{306}', '\u{12d}'), ('\u{69}', '\u{308}', '\u{ef}'),
+    ('\u{69}', '\u{309}', '\u{1ec9}'), ('\u{69}', '\u{30c}', '\u{1d0}'), ('\u{69}', '\u{30f}', '\u{209}'),
+    ('\u{69}', '\u{311}', '\u{20b}'), ('\u{69}', '\u{323}', '\u{1ecb}'), ('\u{69}', '\u{328}', '\u{12f}'),
+    ('\u{69}', '\u{330}', '\u{1e2d}'), ('\u{6a}', '\u{302}', '\u{135}'), ('\u{6a}', '\u{30c}', '\u{1f0}'),
+    ('\u{6b}', '\u{301}', '\u{1e31}'), ('\u{6b}', '\u{30c}', '\u{1e9}'), ('\u{6b}', '\u{323}', '\u{1e33}'),
+    ('\u{6b}', '\u{327}', '\u{137}'), ('\u{6b}', '\u{331}', '\u{1e35}'), ('\u{6c}', '\u{301}', '\u{13a}'),
+    ('\u{6c}', '\u{30c}', '\u{13e}'), ('\u{6c}', '\u{323}', '\u{1e37}'), ('\u{6c}', '\u{327}', '\u{13c}'),
+    ('\u{6c}', '\u{32d}', '\u{1e3d}'), ('\u{6c}', '\u{331}', '\u{1e3b}'), ('\u{6d}', '\u{301}', '\u{1e3f}'),
+    ('\u{6d}', '\u{307}', '\u{1e41}'), ('\u{6d}', '\u{323}', '\u{1e43}'), ('\u{6e}', '\u{300}', '\u{1f9}'),
+    ('\u{6e}', '\u{301}', '\u{144}'), ('\u{6e}', '\u{303}', '\u{f1}'), ('\u{6e}', '\u{307}', '\u{1e45}'),
+    ('\u{6e}', '\u{30c}', '\u{148}'), ('\u{6e}', '\u{323}', '\u{1e47}'), ('\u{6e}', '\u{327}', '\u{146}'),
+    ('\u{6e}', '\u{32d}', '\u{1e4b}'), ('\u{6e}', '\u{331}', '\u{1e49}'), ('\u{6f}', '\u{300}', '\u{f2}'),
+    ('\u{6f}', '\u{301}', '\u{f3}'), ('\u{6f}', '\u{302}', '\u{f4}'), ('\u{6f}', '\u{303}', '\u{f5}'),
+    ('\u{6f}', '\u{304}', '\u{14d}'), ('\u{6f}', '\u{306}', '\u{14f}'), ('\u{6f}', '\u{307}', '\u{22f}'),
+    ('\u{6f}', '\u{308}', '\u{f6}'), ('\u{6f}', '\u{309}', '\u{1ecf}'), ('\u{6f}', '\u{30b}', '\u{151}'),
+    ('\u{6f}', '\u{30c}', '\u{1d2}'), ('\u{6f}', '\u{30f}', '\u{20d}'), ('\u{6f}', '\u{311}', '\u{20f}'),
+    ('\u{6f}', '\u{31b}', '\u{1a1}'), ('\u{6f}', '\u{323}', '\u{1ecd}'), ('\u{6f}', '\u{328}', '\u{1eb}'),
+    ('\u{70}', '\u{301}', '\u{1e55}'), ('\u{70}', '\u{307}', '\u{1e57}'), ('\u{72}', '\u{301}', '\u{155}'),
+    ('\u{72}', '\u{307}', '\u{1e59}'), ('\u{72}', '\u{30c}', '\u{159}'), ('\u{72}', '\u{30f}', '\u{211}'),
+    ('\u{72}', '\u{311}', '\u{213}'), ('\u{72}', '\u{323}', '\u{1e5b}'), ('\u{72}', '\u{327}', '\u{157}'),
+    ('\u{72}', '\u{331}', '\u{1e5f}'), ('\u{73}', '\u{301}', '\u{15b}'), ('\u{73}', '\u{302}', '\u{15d}'),
+    ('\u{73}', '\u{307}', '\u{1e61}'), ('\u{73}', '\u{30c}', '\u{161}'), ('\u{73}', '\u{323}', '\u{1e63}'),
+    ('\u{73}', '\u{326}', '\u{219}'), ('\u{73}', '\u{327}', '\u{15f}'), ('\u{74}', '\u{307}', '\u{1e6b}'),
+    ('\u{74}', '\u{308}', '\u{1e97}'), ('\u{74}', '\u{30c}', '\u{165}'), ('\u{74}', '\u{323}', '\u{1e6d}'),
+    ('\u{74}', '\u{326}', '\u{21b}'), ('\u{74}', '\u{327}', '\u{163}'), ('\u{74}', '\u{32d}', '\u{1e71}'),
+    ('\u{74}', '\u{331}', '\u{1e6f}'), ('\u{75}', '\u{300}', '\u{f9}'), ('\u{75}', '\u{301}', '\u{fa}'),
+    ('\u{75}', '\u{302}', '\u{fb}'), ('\u{75}', '\u{303}', '\u{169}'), ('\u{75}', '\u{304}', '\u{16b}'),
+    ('\u{75}', '\u{306}', '\u{16d}'), ('\u{75}', '\u{308}', '\u{fc}'), ('\u{75}', '\u{309}', '\u{1ee7}'),
+    ('\u{75}', '\u{30a}', '\u{16f}'), ('\u{75}', '\u{30b}', '\u{171}'), ('\u{75}', '\u{30c}', '\u{1d4}'),
+    ('\u{75}', '\u{30f}', '\u{215}'), ('\u{75}', '\u{311}', '\u{217}'), ('\u{75}', '\u{31b}', '\u{1b0}'),
+    ('\u{75}', '\u{323}', '\u{1ee5}'), ('\u{75}', '\u{324}', '\u{1e73}'), ('\u{75}', '\u{328}', '\u{173}'),
+    ('\u{75}', '\u{32d}', '\u{1e77}'), ('\u{75}', '\u{330}', '\u{1e75}'), ('\u{76}', '\u{303}', '\u{1e7d}'),
+    ('\u{76}', '\u{323}', '\u{1e7f}'), ('\u{77}', '\u{300}', '\u{1e81}'), ('\u{77}', '\u{301}', '\u{1e83}'),
+    ('\u{77}', '\u{302}', '\u{175}'), ('\u{77}', '\u{307}', '\u{1e87}'), ('\u{77}', '\u{308}', '\u{1e85}'),
+    ('\u{77}', '\u{30a}', '\u{1e98}'), ('\u{77}', '\u{323}', '\u{1e89}'), ('\u{78}', '\u{307}', '\u{1e8b}'),
+    ('\u{78}', '\u{308}', '\u{1e8d}'), ('\u{79}', '\u{300}', '\u{1ef3}'), ('\u{79}', '\u{301}', '\u{fd}'),
+    ('\u{79}', '\u{302}', '\u{177}'), ('\u{79}', '\u{303}', '\u{1ef9}'), ('\u{79}', '\u{304}', '\u{233}'),
+    ('\u{79}', '\u{307}', '\u{1e8f}'), ('\u{79}', '\u{308}', '\u{ff}'), ('\u{79}', '\u{309}', '\u{1ef7}'),
+    ('\u{79}', '\u{30a}', '\u{1e99}'), ('\u{79}', '\u{323}', '\u{1ef5}'), ('\u{7a}', '\u{301}', '\u{17a}'),
+    ('\u{7a}', '\u{302}', '\u{1e91}'), ('\u{7a}', '\u{307}', '\u{17c}'), ('\u{7a}', '\u{30c}', '\u{17e}'),
+    ('\u{7a}', '\u{323}', '\u{1e93}'), ('\u{7a}', '\u{331}', '\u{1e95}'), ('\u{c2}', '\u{300}', '\u{1ea6}'),
+    ('\u{c2}', '\u{301}', '\u{1ea4}'), ('\u{c2}', '\u{303}', '\u{1eaa}'), ('\u{c2}', '\u{309}', '\u{1ea8}'),
+    ('\u{c4}', '\u{304}', '\u{1de}'), ('\u{c5}', '\u{301}', '\u{1fa}'), ('\u{c6}', '\u{301}', '\u{1fc}'),
+    ('\u{c6}', '\u{304}', '\u{1e2}'), ('\u{c7}', '\u{301}', '\u{1e08}'), ('\u{ca}', '\u{300}', '\u{1ec0}'),
+    ('\u{ca}', '\u{301}', '\u{1ebe}'), ('\u{ca}', '\u{303}', '\u{1ec4}'), ('\u{ca}', '\u{309}', '\u{1ec2}'),
+    ('\u{cf}', '\u{301}', '\u{1e2e}'), ('\u{d4}', '\u{300}', '\u{1ed2}'), ('\u{d4}', '\u{301}', '\u{1ed0}'),
+    ('\u{d4}', '\u{303}', '\u{1ed6}'), ('\u{d4}', '\u{309}', '\u{1ed4}'), ('\u{d5}', '\u{301}', '\u{1e4c}'),
+    ('\u{d5}', '\u{304}', '\u{22c}'), ('\u{d5}', '\u{308}', '\u{1e4e}'), ('\u{d6}', '\u{304}', '\u{22a}'),
+    ('\u{d8}', '\u{301}', '\u{1fe}'), ('\u{dc}', '\u{300}', '\u{1db}'), ('\u{dc}', '\u{301}', '\u{1d7}'),
+    ('\u{dc}', '\u{304}', '\u{1d5}'), ('\u{dc}', '\u{30c}', '\u{1d9}'), ('\u{e2}', '\u{300}', '\u{1ea7}'),
+    ('\u{e2}', '\u{301}', '\u{1ea5}'), ('\u{e2}', '\u{303}', '\u{1eab}'), ('\u{e2}', '\u{309}', '\u{1ea9}'),
+    ('\u{e4}', '\u{304}', '\u{1df}'), ('\u{e5}', '\u{301}', '\u{1fb}'), ('\u{e6}', '\u{301}', '\u{1fd}'),
+    ('\u{e6}', '\u{304}', '\u{1e3}'), ('\u{e7}', '\u{301}', '\u{1e09}'), ('\u{ea}', '\u{300}', '\u{1ec1}'),
+    ('\u{ea}', '\u{301}', '\u{1ebf}'), ('\u{ea}', '\u{303}', '\u{1ec5}'), ('\u{ea}', '\u{309}', '\u{1ec3}'),
+    ('\u{ef}', '\u{301}', '\u{1e2f}'), ('\u{f4}', '\u{300}', '\u{1ed3}'), ('\u{f4}', '\u{301}', '\u{1ed1}'),
+    ('\u{f4}', '\u{303}', '\u{1ed7}'), ('\u{f4}', '\u{309}', '\u{1ed5}'), ('\u{f5}', '\u{301}', '\u{1e4d}'),
+    ('\u{f5}', '\u{304}', '\u{22d}'), ('\u{f5}', '\u{308}', '\u{1e4f}'), ('\u{f6}', '\u{304}', '\u{22b}'),
+    ('\u{f8}', '\u{301}', '\u{1ff}'), ('\u{fc}', '\u{300}', '\u{1dc}'), ('\u{fc}', '\u{301}', '\u{1d8}'),
+    ('\u{fc}', '\u{304}', '\u{1d6}'), ('\u{fc}', '\u{30c}', '\u{1da}'), ('\u{102}', '\u{300}', '\u{1eb0}'),
+    ('\u{102}', '\u{301}', '\u{1eae}'), ('\u{102}', '\u{303}', '\u{1eb4}'), ('\u{102}', '\u{309}', '\u{1eb2}'),
+    ('\u{103}', '\u{300}', '\u{1eb1}'), ('\u{103}', '\u{301}', '\u{1eaf}'), ('\u{103}', '\u{303}', '\u{1eb5}'),
+    ('\u{103}', '\u{309}', '\u{1eb3}'), ('\u{112}', '\u{300}', '\u{1e14}'), ('\u{112}', '\u{301}', '\u{1e16}'),
+    ('\u{113}', '\u{300}', '\u{1e15}'), ('\u{113}', '\u{301}', '\u{1e17}'), ('\u{14c}', '\u{300}', '\u{1e50}'),
+    ('\u{14c}', '\u{301}', '\u{1e52}'), ('\u{14d}', '\u{300}', '\u{1e51}'), ('\u{14d}', '\u{301}', '\u{1e53}'),
+    ('\u{15a}', '\u{307}', '\u{1e64}'), ('\u{15b}', '\u{307}', '\u{1e65}'), ('\u{160}', '\u{307}', '\u{1e66}'),
+    ('\u{161}', '\u{307}', '\u{1e67}'), ('\u{168}', '\u{301}', '\u{1e78}'), ('\u{169}', '\u{301}', '\u{1e79}'),
+    ('\u{16a}', '\u{308}', '\u{1e7a}'), ('\u{16b}', '\u{308}', '\u{1e7b}'), ('\u{17f}', '\u{307}', '\u{1e9b}'),
+    ('\u{1a0}', '\u{300}', '\u{1edc}'), ('\u{1a0}', '\u{301}', '\u{1eda}'), ('\u{1a0}', '\u{303}', '\u{1ee0}'),
+    ('\u{1a0}', '\u{309}', '\u{1ede}'), ('\u{1a0}', '\u{323}', '\u{1ee2}'), ('\u{1a1}', '\u{300}', '\u{1edd}'),
+    ('\u{1a1}', '\u{301}', '\u{1edb}'), ('\u{1a1}', '\u{303}', '\u{1ee1}'), ('\u{1a1}', '\u{309}', '\u{1edf}'),
+    ('\u{1a1}', '\u{323}', '\u{1ee3}'), ('\u{1af}', '\u{300}', '\u{1eea}'), ('\u{1af}', '\u{301}', '\u{1ee8}'),
+    ('\u{1af}', '\u{303}', '\u{1eee}'), ('\u{1af}', '\u{309}', '\u{1eec}'), ('\u{1af}', '\u{323}', '\u{1ef0}'),
+    ('\u{1b0}', '\u{300}', '\u{1eeb}'), ('\u{1b0}', '\u{301}', '\u{1ee9}'), ('\u{1b0}', '\u{303}', '\u{1eef}'),
+    ('\u{1b0}', '\u{309}', '\u{1eed}'), ('\u{1b0}', '\u{323}', '\u{1ef1}'), ('\u{1b7}', '\u{30c}', '\u{1ee}'),
+    ('\u{1ea}', '\u{304}', '\u{1ec}'), ('\u{1eb}', '\u{304}', '\u{1ed}'), ('\u{226}', '\u{304}', '\u{1e0}'),
+    ('\u{227}', '\u{304}', '\u{1e1}'), ('\u{228}', '\u{306}', '\u{1e1c}'), ('\u{229}', '\u{306}', '\u{1e1d}'),
+    ('\u{22e}', '\u{304}', '\u{230}'), ('\u{22f}', '\u{304}', '\u{231}'), ('\u{292}', '\u{30c}', '\u{1ef}'),
+    ('\u{1e36}', '\u{304}', '\u{1e38}'), ('\u{1e37}', '\u{304}', '\u{1e39}'), ('\u{1e5a}', '\u{304}', '\u{1e5c}'),
+    ('\u{1e5b}', '\u{304}', '\u{1e5d}'), ('\u{1e62}', '\u{307}', '\u{1e68}'), ('\u{1e63}', '\u{307}', '\u{1e69}'),
+    ('\u{1ea0}', '\u{302}', '\u{1eac}'), ('\u{1ea0}', '\u{306}', '\u{1eb6}'), ('\u{1ea1}', '\u{302}', '\u{1ead}'),
+    ('\u{1ea1}', '\u{306}', '\u{1eb7}'), ('\u{1eb8}', '\u{302}', '\u{1ec6}'), ('\u{1eb9}', '\u{302}', '\u{1ec7}'),
+    ('\u{1ecc}', '\u{302}', '\u{1ed8}'), ('\u{1ecd}', '\u{302}', '\u{1ed9}'),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compose_marks() {
+        assert_eq!("caf\u{e9}", compose("cafe\u{301}"));
+        assert_eq!("caf\u{e9}", compose("caf\u{e9}"));
+        // stacked marks compose one at a time: e + dot below + circumflex
+        assert_eq!("\u{1ec7}", compose("e\u{323}\u{302}"));
+        // no composition for a lone mark or an unknown pair
+        assert_eq!("\u{301}x", compose("\u{301}x"));
+        assert_eq!("q\u{301}", compose("q\u{301}"));
+    }
+
+    #[test]
+    fn mark_order() {
+        // dot below (class 220) goes before circumflex (230), however they're typed
+        assert_eq!("\u{1ec7}", compose("e\u{302}\u{323}"));
+        assert_eq!(compose("e\u{323}\u{302}"), compose("e\u{302}\u{323}"));
+        // a mark that doesn't compose doesn't stop one of a higher class after it
+        assert_eq!("\u{e1}\u{31b}", compose("a\u{31b}\u{301}"));
+        // but one of the same class does
+        assert_eq!("a\u{30b}\u{301}", compose("a\u{30b}\u{301}"));
+        // marks of the same class keep their order
+        assert_eq!("\u{e1}\u{300}", compose("a\u{301}\u{300}"));
+        assert_eq!("\u{e0}\u{301}", compose("a\u{300}\u{301}"));
+    }
+
+    #[test]
+    fn table_is_sorted() {
+        assert!(COMPOSITIONS.windows(2).all(|w| (w[0].0, w[0].1) < (w[1].0, w[1].1)));
+        assert!(COMBINING_CLASSES.windows(2).all(|w| w[0].0 <= w[0].1 && w[0].1 < w[1].0));
+    }
+
+    #[test]
+    fn normalize() {
+        assert_eq!("Cr\u{e8}me", Normalizer::new(false).normalize("  Cre\u{300}me\t"));
+        assert_eq!("cr\u{e8}me", Normalizer::new(true).normalize("CRE\u{300}ME"));
+    }
+}
@@ -0,0 +1,125 @@
+//! Cancelling a run, and watching it as it goes.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crossword::Crossword;
+
+/// Stops a generator's run from another thread, e.g. a GUI's stop button. Clones
+/// share the same flag.
+///
+/// The search checks it before every candidate placement, and stops with
+/// `StopReason::Cancelled`. Once cancelled it stays cancelled.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+impl CancelToken {
+    /// A token that isn't cancelled yet.
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+    /// Stops every generator sharing the token, before their next placement.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+    /// Whether `cancel` has been called on the token or a clone of it.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Is told what a generator's search is doing as it runs, e.g. to show the layout
+/// so far. Set it with `Generator::with_observer`.
+///
+/// It's called from the searching thread, or threads with `ParIter`, so it should
+/// be quick and pass anything slow on to another thread.
+pub trait Observer: Send + Sync {
+    /// A word was added to a layout, making this one.
+    fn placed(&self, _crossword: &Crossword, _word_index: usize) {}
+    /// The search is done with a placed layout and everything built on it, and goes
+    /// back to the layout before it. Only `Search` (`Generator::iter` with the dfs
+    /// strategy, and `Generator::search`) goes back a word at a time.
+    fn backtracked(&self, _crossword: &Crossword) {}
+    /// A crossword was found, and is about to be yielded.
+    fn solution(&self, _crossword: &Crossword) {}
+    /// A solution with a lower cost than any before it, by the generator's score or
+    /// else by area.
+    fn new_best(&self, _crossword: &Crossword, _cost: f64) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use generate::Generator;
+    use config::Config;
+    use budget::StopReason;
+    use parallel::ParIter;
+
+    const WORDS: [&str; 4] = ["toon", "took", "noob", "koob"];
+
+    #[derive(Default)]
+    struct Counts {
+        placed: usize,
+        backtracked: usize,
+        solutions: usize,
+        best_costs: Vec<f64>
+    }
+    #[derive(Default)]
+    struct Counter {
+        counts: Mutex<Counts>,
+        // cancels after this many solutions
+        cancel_after: Option<(usize, CancelToken)>
+    }
+    impl Observer for Counter {
+        fn placed(&self, _crossword: &Crossword, _word_index: usize) {
+            self.counts.lock().unwrap().placed += 1;
+        }
+        fn backtracked(&self, _crossword: &Crossword) {
+            self.counts.lock().unwrap().backtracked += 1;
+        }
+        fn solution(&self, _crossword: &Crossword) {
+            let mut counts = self.counts.lock().unwrap();
+            counts.solutions += 1;
+            if let Some((n, ref cancel)) = self.cancel_after {
+                if counts.solutions == n {
+                    cancel.cancel();
+                }
+            }
+        }
+        fn new_best(&self, _crossword: &Crossword, cost: f64) {
+            self.counts.lock().unwrap().best_costs.push(cost);
+        }
+    }
+
+    #[test]
+    fn observer() {
+        let counter = Arc::new(Counter::default());
+        let gen = Generator::with_config(&WORDS, Config::default()).unwrap().with_observer(counter.clone());
+        assert_eq!(22, gen.iter().count());
+        let counts = counter.counts.lock().unwrap();
+        assert_eq!(22, counts.solutions);
+        assert_eq!(gen.stats().nodes, counts.placed);
+        // every placed layout is gone back from once the search is done
+        assert_eq!(counts.placed, counts.backtracked);
+        assert!(counts.best_costs.len() > 1);
+        assert!(counts.best_costs.windows(2).all(|pair| pair[1] < pair[0]));
+    }
+
+    #[test]
+    fn cancel() {
+        let gen = Generator::with_config(&WORDS, Config::default()).unwrap();
+        gen.cancel_token().cancel();
+        assert_eq!(0, gen.iter().count());
+        assert_eq!(StopReason::Cancelled, gen.stop_reason());
+
+        // part way through, while worker threads are searching
+        let cancel = CancelToken::new();
+        let counter = Arc::new(Counter { cancel_after: Some((3, cancel.clone())), ..Counter::default() });
+        let gen = Generator::with_config(&WORDS, Config::default()).unwrap()
+            .with_cancel_token(cancel)
+            .with_observer(counter.clone());
+        let gen = Arc::new(gen);
+        assert_eq!(3, ParIter::new(gen.clone(), 2).count());
+        assert_eq!(StopReason::Cancelled, gen.stop_reason());
+    }
+}
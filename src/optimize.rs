@@ -0,0 +1,304 @@
+//! Improving a layout by moving its words around.
+
+use std::collections::HashSet;
+use std::time::Instant;
+
+use crossword::Crossword;
+use placement::Position;
+use generate::Generator;
+use rand::hash;
+
+/// The moves to make if the budget doesn't say. Each move rebuilds the layout once.
+pub const DEFAULT_MOVES: usize = 20000;
+// at the start, a move that makes the cost 5% worse is kept about a third of the time
+const INITIAL_TEMPERATURE: f64 = 0.05;
+
+// more weight placed, then lower cost, is better
+type Cost = (u64, f64);
+
+fn is_better(a: Cost, b: Cost) -> bool {
+    a.0 > b.0 || (a.0 == b.0 && a.1 < b.1)
+}
+
+/// Improves a layout by simulated annealing: each move takes a word out and puts it
+/// back somewhere else, along with any left-out words that now fit. Moves that
+/// make the layout worse are kept less and less often as the run goes on.
+///
+/// Lower cost is better, by the generator's score or else by area, but placing more
+/// weight comes first. Yields the starting layout, then each layout that's better
+/// than every one before it. Locked words stay put, and the run stops after
+/// `max_nodes` moves ([`DEFAULT_MOVES`](constant.DEFAULT_MOVES.html) if not set) or
+/// at the timeout.
+pub struct Optimizer<'a> {
+    gen: &'a Generator,
+    seed: u64,
+    current: (Crossword, Cost),
+    best: Cost,
+    // the start layout, until it's been yielded
+    start: Option<Crossword>,
+    num_moves: usize,
+    max_moves: usize,
+    started_at: Instant
+}
+impl<'a> Optimizer<'a> {
+    pub(crate) fn new(gen: &'a Generator, start: Crossword) -> Optimizer<'a> {
+        gen.tracker().start();
+        let start = fill(gen, start);
+        let start_cost = cost(gen, &start);
+        Optimizer {
+            gen,
+            // hash gives 0 for seed 0, and the moves need random numbers
+            seed: gen.config().seed.wrapping_add(1).max(1),
+            current: (start.clone(), start_cost),
+            best: start_cost,
+            start: Some(start),
+            num_moves: 0,
+            max_moves: gen.config().budget.max_nodes.unwrap_or(DEFAULT_MOVES),
+            started_at: Instant::now()
+        }
+    }
+    /// The moves made so far.
+    pub fn num_moves(&self) -> usize {
+        self.num_moves
+    }
+    /// The weight a layout places, and its cost by the generator's score or else by area.
+    pub fn cost(&self, crossword: &Crossword) -> (u64, f64) {
+        cost(self.gen, crossword)
+    }
+
+    // the i-th random number of the run, from 0 up to but not including 1
+    fn random(&self, i: usize) -> f64 {
+        (hash((self.num_moves, i), self.seed) >> 11) as f64 / (1u64 << 53) as f64
+    }
+    fn random_index(&self, i: usize, n: usize) -> usize {
+        ((self.random(i) * n as f64) as usize).min(n - 1)
+    }
+    // how far through the run it is, by moves or by the clock, whichever is further
+    fn progress(&self) -> f64 {
+        let by_moves = self.num_moves as f64 / self.max_moves as f64;
+        let by_time = self.gen.config().budget.timeout.map_or(0.0, |timeout| {
+            self.started_at.elapsed().as_secs_f64() / timeout.as_secs_f64()
+        });
+        by_moves.max(by_time).min(1.0)
+    }
+
+    // one move: a word moved somewhere else, or None if it can't go anywhere else
+    fn make_move(&self) -> Option<Crossword> {
+        let crossword = &self.current.0;
+        let movable: Vec<usize> = (0..self.gen.num_words())
+            .filter(|&i| crossword.positions.get(i).is_some() && !self.gen.is_locked(i))
+            .collect();
+        if movable.is_empty() {
+            return None
+        }
+        let word_index = movable[self.random_index(0, movable.len())];
+        let old_pos = crossword.positions.get(word_index).unwrap();
+        let rest = self.without(crossword, word_index);
+        if !is_connected(self.gen, &rest) {
+            return None
+        }
+        let placements: Vec<Position> = placements(self.gen, &rest, word_index).into_iter()
+            .filter(|&pos| pos != old_pos)
+            .collect();
+        if placements.is_empty() {
+            return None
+        }
+        // half the time the best place for it, otherwise anywhere
+        let pos = if self.random(1) < 0.5 {
+            placements.into_iter().fold(None, |best: Option<(Position, Cost)>, pos| {
+                let cost = placement_cost(self.gen, &rest, word_index, pos);
+                match best {
+                    Some(best) if !is_better(cost, best.1) => Some(best),
+                    _ => Some((pos, cost))
+                }
+            }).unwrap().0
+        } else {
+            placements[self.random_index(2, placements.len())]
+        };
+        let next = rest.set(self.gen.word(word_index), self.gen.word_chars(word_index).len(), word_index, pos);
+        Some(fill(self.gen, next))
+    }
+    // whether to move from the current layout to next, with the cost of next
+    fn accept(&self, cost: Cost) -> bool {
+        let current = self.current.1;
+        if cost.0 != current.0 {
+            return cost.0 > current.0
+        }
+        let worse = (cost.1 - current.1) / current.1.abs().max(1.0);
+        if worse <= 0.0 {
+            return true
+        }
+        let temperature = INITIAL_TEMPERATURE * (1.0 - self.progress());
+        temperature > 0.0 && self.random(3) < (-worse / temperature).exp()
+    }
+
+    // the layout with a word taken out
+    fn without(&self, crossword: &Crossword, word_index: usize) -> Crossword {
+        crossword.without(word_index, |i| (self.gen.word(i), self.gen.word_chars(i).len()))
+    }
+}
+impl<'a> Iterator for Optimizer<'a> {
+    type Item = Crossword;
+    fn next(&mut self) -> Option<Crossword> {
+        if let Some(start) = self.start.take() {
+            self.gen.found(&start);
+            return Some(start)
+        }
+        let tracker = self.gen.tracker();
+        while self.num_moves < self.max_moves && tracker.check() {
+            let next = self.make_move();
+            self.num_moves += 1;
+            tracker.add_node();
+            let next = match next {
+                Some(next) => next,
+                None => continue
+            };
+            let cost = self.cost(&next);
+            if !self.accept(cost) {
+                continue
+            }
+            self.current = (next, cost);
+            if is_better(cost, self.best) {
+                self.best = cost;
+                self.gen.found(&self.current.0);
+                return Some(self.current.0.clone())
+            }
+        }
+        None
+    }
+}
+
+// every place to add a word across one of the layout's letters that fits the size limit
+fn placements(gen: &Generator, crossword: &Crossword, word_index: usize) -> Vec<Position> {
+    let word = gen.word(word_index);
+    let word_len = gen.word_chars(word_index).len();
+    let bb = crossword.bounding_box();
+    let mut seen: HashSet<Position> = HashSet::new();
+    let mut placements = vec![];
+    for &(c, pos) in crossword.letters() {
+        for &(_, offset) in gen.letter_index().offsets(word_index, c) {
+            let next_pos = pos.from_offset(offset as i8);
+            if next_pos.fits(word_len) && gen.config().size.fits(bb.combine_word_pos(word_len, next_pos))
+                && seen.insert(next_pos) && crossword.can_add_word(word, word_len, next_pos) {
+                placements.push(next_pos);
+            }
+        }
+    }
+    placements
+}
+// the cost of the layout with a word added. only a score needs the layout built,
+// the area comes from the bounding boxes
+fn placement_cost(gen: &Generator, crossword: &Crossword, word_index: usize, pos: Position) -> Cost {
+    let word_len = gen.word_chars(word_index).len();
+    let weight = gen.placed_weight(crossword) + gen.weight(word_index) as u64;
+    if gen.filter().has_score() {
+        let next = crossword.set(gen.word(word_index), word_len, word_index, pos);
+        return (weight, gen.score(&next).unwrap())
+    }
+    (weight, crossword.bounding_box().combine_word_pos(word_len, pos).area() as f64)
+}
+// adds the left-out words where they fit best, in word list order, until none fit
+pub(crate) fn fill(gen: &Generator, crossword: Crossword) -> Crossword {
+    let mut crossword = crossword;
+    loop {
+        let mut is_added = false;
+        for word_index in 0..gen.num_words() {
+            if crossword.positions.get(word_index).is_some() {
+                continue
+            }
+            let best = placements(gen, &crossword, word_index).into_iter().fold(None, |best: Option<(Position, f64)>, pos| {
+                let cost = placement_cost(gen, &crossword, word_index, pos).1;
+                match best {
+                    Some(best) if cost >= best.1 => Some(best),
+                    _ => Some((pos, cost))
+                }
+            });
+            if let Some((pos, _)) = best {
+                crossword = crossword.set(gen.word(word_index), gen.word_chars(word_index).len(), word_index, pos);
+                is_added = true;
+            }
+        }
+        if !is_added {
+            return crossword
+        }
+    }
+}
+
+fn cost(gen: &Generator, crossword: &Crossword) -> Cost {
+    let cost = gen.score(crossword).unwrap_or(crossword.bounding_box().area() as f64);
+    (gen.placed_weight(crossword), cost)
+}
+
+// whether every placed word is joined to the others through crossings
+fn is_connected(gen: &Generator, crossword: &Crossword) -> bool {
+    crossword.first_disconnected(|i| gen.word_chars(i).len()).is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use config::Config;
+    use placement::Direction::{Horizontal, Vertical};
+    use error::CrosswordError;
+
+    const WORDS: [&str; 8] = ["scent", "scarf", "fleet", "tenet", "eerie", "safe", "fret", "teas"];
+
+    fn make_generator() -> Generator {
+        let mut config = Config::new(0, 3);
+        config.budget.max_nodes = Some(2000);
+        Generator::with_config(&WORDS, config).unwrap()
+    }
+
+    #[test]
+    fn improves() {
+        let gen = make_generator();
+        let start = gen.greedy();
+        assert!(start.is_valid());
+        let crosswords: Vec<_> = gen.optimize(start.clone()).unwrap().collect();
+        assert_eq!(start, crosswords[0]);
+        let costs: Vec<_> = crosswords.iter().map(|crossword| cost(&gen, crossword)).collect();
+        assert!(costs.windows(2).all(|pair| is_better(pair[1], pair[0])), "{:?}", costs);
+        let best = crosswords.last().unwrap();
+        assert!(best.is_valid() && is_connected(&gen, best));
+        assert!(gen.left_out(best).next().is_none());
+
+        // the same seed makes the same moves
+        let again: Vec<_> = make_generator().optimize(start).unwrap().collect();
+        assert_eq!(crosswords, again);
+    }
+
+    #[test]
+    fn locked() {
+        let pos = Position { row: 2, col: 1, dir: Vertical };
+        let gen = make_generator().with_locked(&[("tenet", pos)]).unwrap();
+        let best = gen.optimize(gen.greedy()).unwrap().last().unwrap();
+        assert_eq!(Some(pos), best.positions.get(3));
+    }
+
+    #[test]
+    fn connected() {
+        let gen = Generator::with_config(&["hello", "world", "yo"], Config::default()).unwrap();
+        let crossword = Crossword::from_positions(&["hello", "world", "yo"], &[
+            Position { row: 3, col: 0, dir: Horizontal },
+            Position { row: 0, col: 2, dir: Vertical },
+            Position { row: 6, col: 6, dir: Vertical }
+        ]).unwrap();
+        assert!(!is_connected(&gen, &crossword));
+        let optimizer = gen.optimize(crossword.clone()).unwrap();
+        assert!(is_connected(&gen, &optimizer.without(&crossword, 2)));
+        assert!(gen.optimize(Crossword::new(2)).is_err());
+    }
+
+    #[test]
+    fn start_without_locked() {
+        let pos = Position { row: 2, col: 1, dir: Vertical };
+        let gen = make_generator().with_locked(&[("tenet", pos)]).unwrap();
+        let start = make_generator().greedy();
+        assert_ne!(Some(pos), start.positions.get(3));
+        match gen.optimize(start) {
+            Err(CrosswordError::LockedWordMissing { index, pos: locked_pos, .. }) => assert_eq!((3, pos), (index, locked_pos)),
+            other => panic!("expected LockedWordMissing, got {:?}", other.map(|_| ()))
+        }
+        assert!(gen.optimize(gen.greedy()).is_ok());
+    }
+}
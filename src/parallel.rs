@@ -0,0 +1,173 @@
+//! Searching on several threads.
+
+use std::cmp::max;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::thread;
+
+use crossword::Crossword;
+use generate::Generator;
+use search::{Search, Step};
+
+// the search tree is split into about this many subtrees per thread, so that
+// threads that finish early can take more work
+const SUBTREES_PER_THREAD: usize = 32;
+// crosswords buffered per thread before the workers wait for the consumer
+const RESULTS_PER_THREAD: usize = 64;
+
+type State = (Crossword, Arc<Vec<usize>>);
+
+/// Runs a generator's search on several threads, yielding crosswords as they are found.
+///
+/// The search tree is split breadth first into subtrees, which the worker threads
+/// take from a shared queue. The workers share the generator's seen set and area
+/// bounds, so each crossword is still found once, but in no particular order.
+/// Dropping the iterator stops the workers before their next placement.
+pub struct ParIter {
+    gen: Arc<Generator>,
+    results: Receiver<Crossword>,
+    is_dropped: Arc<AtomicBool>
+}
+impl ParIter {
+    /// Starts searching on `num_threads` threads, at least one.
+    pub fn new(gen: Arc<Generator>, num_threads: usize) -> ParIter {
+        let num_threads = max(num_threads, 1);
+        gen.tracker().start();
+        let seed = gen.get_seed();
+        let subtrees = split(&gen, gen.get_inits(), seed, num_threads * SUBTREES_PER_THREAD);
+        let subtrees = Arc::new(Mutex::new(subtrees));
+        let is_dropped = Arc::new(AtomicBool::new(false));
+        let (sender, results) = sync_channel(num_threads * RESULTS_PER_THREAD);
+        for _ in 0..num_threads {
+            let (gen, subtrees, is_dropped, sender) = (gen.clone(), subtrees.clone(), is_dropped.clone(), sender.clone());
+            thread::spawn(move || {
+                while !is_dropped.load(Ordering::Relaxed) {
+                    let subtree = subtrees.lock().unwrap().pop_front();
+                    let (crossword, candidates) = match subtree {
+                        Some(subtree) => subtree,
+                        None => return
+                    };
+                    let mut search = Search::subtree(&gen, crossword, candidates, seed);
+                    while !is_dropped.load(Ordering::Relaxed) {
+                        match search.step() {
+                            Step::Found(crossword) => if sender.send(crossword).is_err() {
+                                return
+                            },
+                            Step::Searching => {},
+                            Step::Done => break
+                        }
+                    }
+                }
+            });
+        }
+        ParIter {
+            gen,
+            results,
+            is_dropped
+        }
+    }
+}
+impl Iterator for ParIter {
+    type Item = Crossword;
+    fn next(&mut self) -> Option<Crossword> {
+        // crosswords still buffered when the budget ran out aren't yielded
+        if self.gen.tracker().is_stopped() {
+            return None
+        }
+        loop {
+            let crossword = self.results.recv().ok()?;
+            // another thread may have found a partial solution placing more weight
+            // since this one was sent
+            if self.gen.config().partial && self.gen.placed_weight(&crossword) < self.gen.best_partial() {
+                continue
+            }
+            self.gen.found(&crossword);
+            return Some(crossword)
+        }
+    }
+}
+impl Drop for ParIter {
+    fn drop(&mut self) {
+        self.is_dropped.store(true, Ordering::Relaxed);
+    }
+}
+
+// expands the tree breadth first, one subtree at a time, until there are enough
+// subtrees, or every subtree is a leaf. each expansion places one more word, so
+// this stops after about num_subtrees of them. leaves are kept for the workers to
+// yield, see Generator::leaf.
+fn split(gen: &Generator, inits: Vec<State>, seed: u64, num_subtrees: usize) -> VecDeque<State> {
+    let mut subtrees: VecDeque<State> = inits.into_iter().collect();
+    let mut leaves = vec![];
+    while subtrees.len() + leaves.len() < num_subtrees && !gen.tracker().is_stopped() {
+        let (crossword, candidates) = match subtrees.pop_front() {
+            Some(subtree) => subtree,
+            None => break
+        };
+        let next: Vec<State> = gen.children(crossword.clone(), candidates.clone(), seed).collect();
+        if next.is_empty() {
+            leaves.push((crossword, candidates));
+        }
+        subtrees.extend(next);
+    }
+    subtrees.extend(leaves);
+    subtrees
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use budget::{Budget, StopReason};
+
+    fn make_generator(num_areas: usize) -> Arc<Generator> {
+        Arc::new(Generator::new(&["toon", "took", "noob", "koob"], num_areas, 0).unwrap())
+    }
+
+    #[test]
+    fn same_crosswords() {
+        let expected: HashSet<_> = make_generator(0).iter().map(|crossword| crossword.positions).collect();
+        for &num_threads in &[1, 2, 8] {
+            let crosswords: Vec<_> = ParIter::new(make_generator(0), num_threads).map(|crossword| crossword.positions).collect();
+            assert_eq!(22, crosswords.len());
+            assert_eq!(expected, crosswords.into_iter().collect());
+        }
+    }
+
+    #[test]
+    fn partial() {
+        use config::Config;
+        let config = Config { partial: true, ..Config::default() };
+        let gen = Arc::new(Generator::with_config(&["toon", "took", "noob", "koob", "zzz"], config).unwrap());
+        let crosswords: Vec<_> = ParIter::new(gen.clone(), 4).collect();
+        assert_eq!(22, crosswords.iter().filter(|crossword| gen.placed_weight(crossword) == 4).count());
+        // each places at least as much weight as the ones before
+        let weights: Vec<_> = crosswords.iter().map(|crossword| gen.placed_weight(crossword)).collect();
+        assert!(weights.windows(2).all(|pair| pair[0] <= pair[1]), "{:?}", weights);
+    }
+
+    #[test]
+    fn dropped() {
+        use std::time::{Duration, Instant};
+        // zzz can't be placed, so the workers search for a long time without finding anything
+        let words = ["apple", "orange", "banana", "cherry", "grape", "lemon", "melon", "peach", "pearl", "river", "stone", "table", "zzz"];
+        let gen = Arc::new(Generator::new(&words, 0, 0).unwrap());
+        drop(ParIter::new(gen.clone(), 4));
+        // each worker holds the generator until it stops
+        let start = Instant::now();
+        while Arc::strong_count(&gen) > 1 {
+            assert!(start.elapsed() < Duration::from_secs(10), "the workers are still searching");
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn budget() {
+        let gen = Arc::new(Generator::new(&["toon", "took", "noob", "koob"], 0, 0).unwrap()
+            .with_budget(Budget { max_results: Some(5), ..Budget::default() }));
+        assert_eq!(5, ParIter::new(gen.clone(), 4).count());
+        assert_eq!(StopReason::MaxResults, gen.stop_reason());
+    }
+}
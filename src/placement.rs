@@ -1,17 +1,35 @@
+//! Where words go: positions and directions.
+
+use error::CrosswordError;
+
+/// Rows and cols of placed words stay within ±MAX_COORD, so the cells around a word
+/// of up to MAX_WORD_LEN letters still fit in an i8, and any area fits in an i16.
+pub const MAX_COORD: i8 = 60;
+/// The most letters a word can have.
+pub const MAX_WORD_LEN: usize = 60;
+
+/// Which way a word reads.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum Direction {
+    /// Across, left to right.
     Horizontal,
+    /// Down, top to bottom.
     Vertical
 }
 use self::Direction::*;
 
+/// Where a word starts, and which way it reads.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct Position {
+    /// The row of the first letter, growing down.
     pub row: i8,
+    /// The col of the first letter, growing right.
     pub col: i8,
+    /// Which way the word reads.
     pub dir: Direction
 }
 impl Position {
+    /// The position of the word's letter `i`, reading the same way.
     pub fn letter_pos(&self, i: i8) -> Position {
         match self.dir {
             Horizontal => Position {
@@ -26,6 +44,18 @@ impl Position {
             }
         }
     }
+    /// Whether a word of `word_len` letters starting here stays within ±MAX_COORD.
+    pub fn fits(&self, word_len: usize) -> bool {
+        let in_range = |x: i16| x >= -(MAX_COORD as i16) && x <= MAX_COORD as i16;
+        let (row, col) = (self.row as i16, self.col as i16);
+        let last = word_len as i16 - 1;
+        let (last_row, last_col) = match self.dir {
+            Horizontal => (row, col + last),
+            Vertical => (row + last, col)
+        };
+        word_len <= MAX_WORD_LEN && in_range(row) && in_range(col) && in_range(last_row) && in_range(last_col)
+    }
+    /// The position of a word across this one, whose letter `i` is this one's first.
     pub fn from_offset(&self, i: i8) -> Position {
         match self.dir {
             Horizontal => Position {
@@ -41,6 +71,7 @@ impl Position {
         }
     }
 }
+/// Where the first word of a search goes.
 pub const START_POSITION: Position = Position { row: 0, col: 0, dir: Horizontal };
 
 use std::fmt::{Display, Formatter, Result};
@@ -52,3 +83,62 @@ impl Display for Position {
         })
     }
 }
+
+use std::str::FromStr;
+impl FromStr for Position {
+    type Err = CrosswordError;
+    // row,col,dir where dir is H or V, as printed by Display
+    fn from_str(s: &str) -> ::std::result::Result<Position, CrosswordError> {
+        let invalid = |reason: String| CrosswordError::InvalidPosition { input: s.to_string(), reason };
+        let parts: Vec<&str> = s.split(',').map(|part| part.trim()).collect();
+        if parts.len() != 3 {
+            return Err(invalid("expected row,col,dir".to_string()))
+        }
+        let row = parts[0].parse::<i8>().map_err(|e| invalid(format!("row '{}': {}", parts[0], e)))?;
+        let col = parts[1].parse::<i8>().map_err(|e| invalid(format!("col '{}': {}", parts[1], e)))?;
+        let dir = match parts[2] {
+            "H" | "h" => Horizontal,
+            "V" | "v" => Vertical,
+            dir => return Err(invalid(format!("dir '{}': expected H or V", dir)))
+        };
+        Ok(Position { row, col, dir })
+    }
+}
+/// Parses a flat list of row,col,dir triples, e.g. 3,0,H,0,2,V.
+pub fn parse_positions(s: &str) -> ::std::result::Result<Vec<Position>, CrosswordError> {
+    let parts: Vec<&str> = s.split(',').collect();
+    if !parts.len().is_multiple_of(3) {
+        return Err(CrosswordError::InvalidPosition {
+            input: s.to_string(),
+            reason: format!("expected row,col,dir triples but got {} values", parts.len())
+        })
+    }
+    parts.chunks(3).map(|triple| triple.join(",").parse()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(Ok(Position { row: 3, col: -1, dir: Vertical }), "3,-1,V".parse());
+        assert!("3,0".parse::<Position>().is_err());
+        assert!("3,0,X".parse::<Position>().is_err());
+        assert!("300,0,H".parse::<Position>().is_err());
+
+        let start = Position { row: 0, col: 2, dir: Horizontal };
+        assert_eq!(Ok(vec![START_POSITION, start]), parse_positions("0,0,H,0,2,H"));
+        assert!(parse_positions("0,0,H,0").is_err());
+    }
+
+    #[test]
+    fn fits() {
+        assert!(START_POSITION.fits(MAX_WORD_LEN));
+        assert!(!START_POSITION.fits(MAX_WORD_LEN + 1));
+        assert!(Position { row: -MAX_COORD, col: MAX_COORD - 4, dir: Horizontal }.fits(5));
+        assert!(!Position { row: -MAX_COORD, col: MAX_COORD - 4, dir: Horizontal }.fits(6));
+        assert!(!Position { row: 0, col: -MAX_COORD - 1, dir: Vertical }.fits(2));
+        assert!(!Position { row: 127, col: 0, dir: Vertical }.fits(2));
+    }
+}
@@ -1,34 +1,157 @@
-use std::hash::{Hash, Hasher};
-use std::collections::hash_map::DefaultHasher;
+use placement::Position;
+use placement::Direction::{Horizontal, Vertical};
+use word_placements::WordPlacements;
 
-pub fn hash<T: Hash>(t: T, seed: u64) -> u64 {
+// bumped whenever hash or rand_range change what they return, which changes the
+// layouts a seed gives. 1 was std's DefaultHasher, which could change with Rust,
+// and 2's rand_range skipped some indexes and repeated others.
+pub const RAND_VERSION: u32 = 3;
+
+// SplitMix64's output function
+fn mix(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+// mixes in one u64 at a time, so a value hashes the same on every platform and
+// Rust release
+pub struct StableHasher(u64);
+impl StableHasher {
+    pub fn new(seed: u64) -> StableHasher {
+        StableHasher(mix(seed))
+    }
+    pub fn write(&mut self, x: u64) {
+        self.0 = mix(self.0 ^ x);
+    }
+    pub fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+// what goes into the hash of a value, spelled out rather than derived
+pub trait StableHash {
+    fn stable_hash(&self, hasher: &mut StableHasher);
+}
+impl StableHash for u64 {
+    fn stable_hash(&self, hasher: &mut StableHasher) {
+        hasher.write(*self);
+    }
+}
+impl StableHash for usize {
+    fn stable_hash(&self, hasher: &mut StableHasher) {
+        hasher.write(*self as u64);
+    }
+}
+impl StableHash for char {
+    fn stable_hash(&self, hasher: &mut StableHasher) {
+        hasher.write(*self as u64);
+    }
+}
+impl StableHash for Position {
+    fn stable_hash(&self, hasher: &mut StableHasher) {
+        // rows and cols are small, so these can't run into each other
+        let dir = match self.dir { Horizontal => 0, Vertical => 1 };
+        hasher.write(((self.row as u8 as u64) << 16) | ((self.col as u8 as u64) << 8) | dir);
+    }
+}
+impl StableHash for WordPlacements {
+    fn stable_hash(&self, hasher: &mut StableHasher) {
+        hasher.write(self.as_slice().len() as u64);
+        for opt_pos in self.as_slice() {
+            match *opt_pos {
+                Some(pos) => pos.stable_hash(hasher),
+                None => hasher.write(u64::MAX)
+            }
+        }
+    }
+}
+impl<T: StableHash + ?Sized> StableHash for &T {
+    fn stable_hash(&self, hasher: &mut StableHasher) {
+        (**self).stable_hash(hasher);
+    }
+}
+impl<A: StableHash, B: StableHash> StableHash for (A, B) {
+    fn stable_hash(&self, hasher: &mut StableHasher) {
+        self.0.stable_hash(hasher);
+        self.1.stable_hash(hasher);
+    }
+}
+impl<A: StableHash, B: StableHash, C: StableHash> StableHash for (A, B, C) {
+    fn stable_hash(&self, hasher: &mut StableHasher) {
+        self.0.stable_hash(hasher);
+        self.1.stable_hash(hasher);
+        self.2.stable_hash(hasher);
+    }
+}
+
+pub fn hash<T: StableHash>(t: T, seed: u64) -> u64 {
     if seed == 0 {
         return 0
     }
-    let mut s = DefaultHasher::new();
-    (t, seed).hash(&mut s);
+    let mut s = StableHasher::new(seed);
+    t.stable_hash(&mut s);
     s.finish()
 }
 
-// two param 1-1 mapping of a range of ints
+// two param 1-1 mapping of a range of ints: i * stride + offset, mod n, with a
+// stride that shares no factor with n, so every index comes up exactly once
 pub fn rand_range(n: usize, seed: u64) -> impl Fn(usize) -> usize {
-    let (offset, stride) = if n > 1 && seed != 0 {
-        let seed = seed as usize;
-        (seed % n, (seed / n) % (n - 1) + 1)
-    } else {
-        (0, 0)
-    };
-    return move |i| {
+    let (n64, offset, stride) = range_params(n, seed);
+    move |i| {
         if seed == 0 {
             return i
         }
-        let j = i * stride + offset;
-        return (j + j / n) % n;
+        ((i as u64 * stride + offset) % n64) as usize
+    }
+}
+// the inverse of rand_range: where each int comes in the mapping
+pub fn rand_range_inverse(n: usize, seed: u64) -> impl Fn(usize) -> usize {
+    let (n64, offset, stride) = range_params(n, seed);
+    let inverse = mod_inverse(stride, n64);
+    move |j| {
+        if seed == 0 {
+            return j
+        }
+        ((j as u64 + n64 - offset) % n64 * inverse % n64) as usize
+    }
+}
+// n, offset and stride, in u64 so they're the same where usize is 32 bits
+fn range_params(n: usize, seed: u64) -> (u64, u64, u64) {
+    let n64 = n as u64;
+    if n > 1 && seed != 0 {
+        let mut stride = (seed / n64) % (n64 - 1) + 1;
+        while gcd(stride, n64) != 1 {
+            stride = stride % (n64 - 1) + 1;
+        }
+        (n64, seed % n64, stride)
+    } else {
+        (n64.max(1), 0, 1)
+    }
+}
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+// x such that a * x is 1 mod n, for a coprime to n
+fn mod_inverse(a: u64, n: u64) -> u64 {
+    let (mut r0, mut r1) = (n as i64, a as i64);
+    let (mut t0, mut t1) = (0i64, 1i64);
+    while r1 != 0 {
+        let q = r0 / r1;
+        let (r, t) = (r0 - q * r1, t0 - q * t1);
+        r0 = r1;
+        r1 = r;
+        t0 = t1;
+        t1 = t;
     }
+    t0.rem_euclid(n as i64) as u64
 }
 
 #[cfg(test)]
 pub mod tests {
+    use super::*;
+
     #[test]
     fn rand_range() {
         let map_range = super::rand_range(1, 0);
@@ -41,12 +164,60 @@ pub mod tests {
         }
         println!();
 
-        let expected = [1, 3, 2, 0];
-        let map_range = super::rand_range(4, 1 + 4 * 1);
-        for i in 0..4 {
+        let expected = [1, 0, 3, 2];
+        let map_range = super::rand_range(4, 1 + 4);
+        for (i, &expected) in expected.iter().enumerate() {
             print!("{} ", map_range(i));
-            assert_eq!(expected[i], map_range(i));
+            assert_eq!(expected, map_range(i));
         }
         println!();
     }
+
+    // these lock the sequence for RAND_VERSION. if they change, so do the layouts
+    // every published seed gives, and RAND_VERSION has to go up.
+    #[test]
+    fn golden() {
+        assert_eq!(0, hash(5u64, 0));
+        assert_eq!(GOLDEN_MIX, mix(0));
+        assert_eq!(GOLDEN_SEED, hash(1u64, 1));
+        assert_eq!(GOLDEN_CANDIDATE, hash((2usize, 5usize, 1usize), 7));
+        let pos = Position { row: -3, col: 4, dir: Vertical };
+        assert_eq!(GOLDEN_LETTER, hash(('e', pos), 7));
+        let placements = WordPlacements::new(3).set(1, pos);
+        assert_eq!(GOLDEN_PLACEMENTS, hash(&placements, 7));
+        let map_range = super::rand_range(10, hash(1u64, 1));
+        assert_eq!(GOLDEN_RANGE, (0..10).map(map_range).collect::<Vec<_>>()[..]);
+    }
+    // SplitMix64's first output for seed 0
+    const GOLDEN_MIX: u64 = 0xe220_a839_7b1d_cdaf;
+    const GOLDEN_SEED: u64 = 0xe9fd_6049_d65a_f21e;
+    const GOLDEN_CANDIDATE: u64 = 0xf7b3_9721_ca85_1eff;
+    const GOLDEN_LETTER: u64 = 0x5ddf_650a_3900_62fa;
+    const GOLDEN_PLACEMENTS: u64 = 0x75da_4b87_cee5_11cf;
+    const GOLDEN_RANGE: [usize; 10] = [6, 5, 4, 3, 2, 1, 0, 9, 8, 7];
+
+    #[test]
+    fn rand_range_is_a_permutation() {
+        for n in 1..40 {
+            for seed in (0..200).chain((0..50).map(|i| hash(i as u64, 1))) {
+                let map_range = super::rand_range(n, seed);
+                let mut seen = vec![false; n];
+                for i in 0..n {
+                    let j = map_range(i);
+                    assert!(j < n && !seen[j], "n {} seed {} maps twice to {}", n, seed, j);
+                    seen[j] = true;
+                    assert_eq!(i, rand_range_inverse(n, seed)(j));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn distinct() {
+        let a = Position { row: 0, col: 1, dir: Horizontal };
+        let b = Position { row: 1, col: 0, dir: Horizontal };
+        assert_ne!(hash(a, 7), hash(b, 7));
+        assert_ne!(hash(WordPlacements::new(2).set(0, a), 7), hash(WordPlacements::new(2).set(1, a), 7));
+        assert_ne!(hash((1usize, 2usize), 7), hash((2usize, 1usize), 7));
+    }
 }
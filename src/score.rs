@@ -0,0 +1,187 @@
+//! Costs to rank and prune layouts by.
+
+use std::cmp::{min, max};
+
+use crossword::Crossword;
+use bounding_box::BoundingBox;
+
+/// A cost to rank finished layouts by; lower is better.
+///
+/// With `num_areas` set to k, the generator keeps to layouts that are in the top k
+/// by cost so far. A score that can give a `lower_bound` also lets it prune the
+/// layouts that can't get there. Implement it to plug in your own objective with
+/// `Generator::with_score`.
+pub trait Score: Send + Sync {
+    /// The layout's cost.
+    fn cost(&self, crossword: &Crossword) -> f64;
+    /// The lowest cost of any layout that covers at least this bounding box, if
+    /// the score can tell.
+    fn lower_bound(&self, _bb: BoundingBox) -> Option<f64> {
+        None
+    }
+    /// The score as set in the config, and echoed in the header.
+    fn name(&self) -> String;
+}
+
+/// The built-in scores.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Metric {
+    /// Width times height.
+    Area,
+    /// More crossings is better.
+    Overlaps,
+    /// More of the bounding box filled with letters is better.
+    Density,
+    /// Closer to a square is better.
+    Squareness,
+    /// Twice the width plus twice the height.
+    Perimeter
+}
+impl Score for Metric {
+    fn cost(&self, crossword: &Crossword) -> f64 {
+        let bb = crossword.bounding_box();
+        match *self {
+            Metric::Area | Metric::Perimeter => self.lower_bound(bb).unwrap(),
+            Metric::Overlaps => -(crossword.num_overlaps() as f64),
+            Metric::Density => 1.0 - crossword.num_letters() as f64 / bb.area() as f64,
+            Metric::Squareness => 1.0 - min(bb.width(), bb.height()) as f64 / max(bb.width(), bb.height()) as f64
+        }
+    }
+    // a layout's bounding box only grows as words are added
+    fn lower_bound(&self, bb: BoundingBox) -> Option<f64> {
+        match *self {
+            Metric::Area => Some(bb.area() as f64),
+            Metric::Perimeter => Some(2.0 * (bb.width() + bb.height()) as f64),
+            Metric::Overlaps | Metric::Density | Metric::Squareness => None
+        }
+    }
+    fn name(&self) -> String {
+        match *self {
+            Metric::Area => "area",
+            Metric::Overlaps => "overlaps",
+            Metric::Density => "density",
+            Metric::Squareness => "squareness",
+            Metric::Perimeter => "perimeter"
+        }.to_string()
+    }
+}
+
+/// A weighted sum of built-in scores, written `metric[:weight],...`, e.g.
+/// `area:1,overlaps:10`. Weights default to 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Weighted {
+    /// Each score and its weight.
+    pub terms: Vec<(Metric, f64)>
+}
+// for Config's Eq: parsing turns down weights that aren't finite, so no weight is
+// NaN unless a caller builds the terms with one by hand
+impl Eq for Weighted {}
+impl Score for Weighted {
+    fn cost(&self, crossword: &Crossword) -> f64 {
+        self.terms.iter().map(|&(metric, weight)| weight * metric.cost(crossword)).sum()
+    }
+    // only bounded if every term is, and none counts against the others
+    fn lower_bound(&self, bb: BoundingBox) -> Option<f64> {
+        self.terms.iter().try_fold(0.0, |sum, &(metric, weight)| {
+            match metric.lower_bound(bb) {
+                Some(bound) if weight >= 0.0 => Some(sum + weight * bound),
+                _ => None
+            }
+        })
+    }
+    fn name(&self) -> String {
+        self.to_string()
+    }
+}
+
+use std::fmt::{Display, Formatter, Result};
+impl Display for Weighted {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        for (i, &(metric, weight)) in self.terms.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", metric.name())?;
+            if weight != 1.0 {
+                write!(f, ":{}", weight)?;
+            }
+        }
+        write!(f, "")
+    }
+}
+use std::str::FromStr;
+impl FromStr for Weighted {
+    type Err = String;
+    fn from_str(s: &str) -> ::std::result::Result<Weighted, String> {
+        let terms = s.split(',').map(|term| {
+            let (name, weight) = match term.find(':') {
+                Some(i) => (term[..i].trim(), Some(term[i + 1..].trim())),
+                None => (term.trim(), None)
+            };
+            let metric = match name {
+                "area" => Metric::Area,
+                "overlaps" => Metric::Overlaps,
+                "density" => Metric::Density,
+                "squareness" => Metric::Squareness,
+                "perimeter" => Metric::Perimeter,
+                _ => return Err(format!("unknown score '{}', expected area, overlaps, density, squareness or perimeter", name))
+            };
+            let weight = match weight {
+                Some(weight) => weight.parse::<f64>().map_err(|e| format!("weight '{}': {}", weight, e))?,
+                None => 1.0
+            };
+            if !weight.is_finite() {
+                return Err(format!("weight '{}' isn't a number", weight))
+            }
+            Ok((metric, weight))
+        }).collect::<::std::result::Result<Vec<_>, String>>()?;
+        Ok(Weighted { terms })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossword::tests::make_crossword;
+    use placement::Position;
+    use placement::Direction::{Horizontal, Vertical};
+
+    //   0 1 2 3 4
+    // 0     w
+    // 1     o
+    // 2     r
+    // 3 h e l l o
+    // 4     d
+    fn hello_world() -> Crossword {
+        make_crossword(vec![
+            ("hello", Position { row: 3, col: 0, dir: Horizontal }),
+            ("world", Position { row: 0, col: 2, dir: Vertical })
+        ])
+    }
+
+    #[test]
+    fn metrics() {
+        let crossword = hello_world();
+        assert_eq!(25.0, Metric::Area.cost(&crossword));
+        assert_eq!(-1.0, Metric::Overlaps.cost(&crossword));
+        assert_eq!(1.0 - 9.0 / 25.0, Metric::Density.cost(&crossword));
+        assert_eq!(0.0, Metric::Squareness.cost(&crossword));
+        assert_eq!(20.0, Metric::Perimeter.cost(&crossword));
+        assert_eq!(None, Metric::Overlaps.lower_bound(crossword.bounding_box()));
+    }
+
+    #[test]
+    fn weighted() {
+        let score: Weighted = "area, overlaps:10".parse().unwrap();
+        assert_eq!(vec![(Metric::Area, 1.0), (Metric::Overlaps, 10.0)], score.terms);
+        assert_eq!("area,overlaps:10", score.to_string());
+        assert_eq!(15.0, score.cost(&hello_world()));
+        // overlaps can't be bounded, so neither can the sum
+        assert_eq!(None, score.lower_bound(BoundingBox::new(0, 0, 1, 1)));
+        let score: Weighted = "area:2,perimeter".parse().unwrap();
+        assert_eq!(Some(16.0), score.lower_bound(BoundingBox::new(0, 0, 1, 1)));
+        assert!("volume".parse::<Weighted>().is_err());
+        assert!("area:x".parse::<Weighted>().is_err());
+        assert!("area:inf".parse::<Weighted>().is_err());
+    }
+}
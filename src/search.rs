@@ -0,0 +1,588 @@
+//! A depth-first search that can be saved and resumed.
+
+use std::sync::Arc;
+use std::cmp::max;
+
+use crossword::Crossword;
+use bounding_box::BoundingBox;
+use placement::{Position, START_POSITION};
+use generate::{Generator, Candidate, Crossing, frame_seed, word_order};
+use checkpoint::{Checkpoint, FrameState};
+use letter_index::GridLetters;
+use seen::SeenSet;
+use rand::RAND_VERSION;
+use error::CrosswordError;
+
+/// How far one level of a search has got through its candidate placements: the
+/// word to place, the letter of the layout to cross, and the letter of the word
+/// to cross it with, each counting through that level's shuffled order.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct Cursor {
+    /// How many of the words to place it has gone past.
+    pub word: usize,
+    /// How many of the layout's letters it has gone past, for the word.
+    pub letter: usize,
+    /// How many of the word's letters it has gone past, for the layout's letter.
+    pub word_char: usize
+}
+impl Cursor {
+    fn next_letter(&mut self, letters_len: usize) {
+        self.word_char = 0;
+        self.letter += 1;
+        if self.letter == letters_len {
+            self.letter = 0;
+            self.word += 1;
+        }
+    }
+}
+
+// multi_iter's searches share out the layouts with at most this many words left
+const SPLIT_AT: usize = 6;
+
+// one level of the depth-first search
+struct Frame {
+    // the word placed on top of the level below, or the anchor word placed first
+    // (at the start position, or where it's locked)
+    placement: (usize, Position),
+    is_anchor: bool,
+    crossword: Crossword,
+    bb: BoundingBox,
+    candidates: Arc<Vec<usize>>,
+    grid: GridLetters,
+    seed: u64,
+    cursor: Cursor,
+    // the crossings of the word the cursor is on, and which word that is
+    crossings: (usize, Vec<Crossing>),
+    // whether a word has been placed on top, if not it's a dead end (see partial_solution)
+    has_children: bool
+}
+impl Frame {
+    fn new(placement: (usize, Position), is_anchor: bool, crossword: Crossword, candidates: Arc<Vec<usize>>, search_seed: u64) -> Frame {
+        Frame {
+            placement,
+            is_anchor,
+            bb: crossword.bounding_box(),
+            seed: frame_seed(&crossword, candidates.len(), search_seed),
+            grid: GridLetters::new(crossword.letters()),
+            crossword,
+            candidates,
+            cursor: Cursor::default(),
+            crossings: (usize::MAX, vec![]),
+            has_children: false
+        }
+    }
+    // whether a cursor points into this level's words and letters, or just past them
+    fn is_valid(&self, gen: &Generator, cursor: Cursor) -> bool {
+        let n = self.candidates.len();
+        if cursor.word >= n {
+            return cursor == Cursor { word: n, letter: 0, word_char: 0 }
+        }
+        let w = gen.candidate(&self.candidates, word_order(n, self.seed)(cursor.word));
+        cursor.letter < self.crossword.letters().len() && cursor.word_char < w.1
+    }
+    // the candidate placement at or after the cursor, moving the cursor on past it.
+    // only visits the placements where the letters match.
+    fn next_placement(&mut self, gen: &Generator) -> Option<(Candidate, (char, Position), usize)> {
+        let n = self.candidates.len();
+        let letters = self.crossword.letters();
+        if letters.is_empty() {
+            return None
+        }
+        while self.cursor.word < n {
+            let Cursor { word, letter, word_char } = self.cursor;
+            let w = gen.candidate(&self.candidates, word_order(n, self.seed)(word));
+            if self.crossings.0 != word {
+                self.crossings = (word, gen.crossings(w, letters, &self.grid, self.seed));
+            }
+            let crossings = &self.crossings.1;
+            let next = crossings.partition_point(|&(rank, _, _)| rank < (letter, word_char));
+            match crossings.get(next) {
+                Some(&((letter_rank, char_rank), letter, i2)) => {
+                    self.cursor = Cursor { word, letter: letter_rank, word_char: char_rank + 1 };
+                    if self.cursor.word_char == w.1 {
+                        self.cursor.next_letter(letters.len());
+                    }
+                    return Some((w, letters[letter], i2))
+                },
+                None => self.cursor = Cursor { word: word + 1, letter: 0, word_char: 0 }
+            }
+        }
+        None
+    }
+}
+
+/// What one step of a [`Search`](struct.Search.html) did.
+pub enum Step {
+    /// It finished a layout.
+    Found(Crossword),
+    /// It tried a placement, or backtracked.
+    Searching,
+    /// The search is finished, or the generator's budget ran out.
+    Done
+}
+
+/// A depth-first search over a generator's layouts that can be saved as a
+/// [`Checkpoint`](../checkpoint/struct.Checkpoint.html) between any two steps,
+/// and resumed from it later. Yields the same crosswords in the same order as
+/// `Generator::iter`.
+pub struct Search<'a> {
+    gen: &'a Generator,
+    seed: u64,
+    stack: Vec<Frame>,
+    // whether finding a crossword counts it, see Generator::found. ParIter counts
+    // them as they're yielded instead.
+    counts_found: bool
+}
+impl<'a> Search<'a> {
+    pub(crate) fn new(gen: &'a Generator) -> Search<'a> {
+        gen.tracker().start();
+        let seed = gen.get_seed();
+        // the first anchor's search goes on top of the stack, so it runs first
+        let stack = gen.anchors().into_iter().rev().map(|anchor| {
+            let (crossword, candidates) = gen.get_init(anchor);
+            Frame::new((anchor, START_POSITION), true, crossword, candidates, seed)
+        }).collect();
+        Search {
+            gen,
+            seed,
+            stack,
+            counts_found: true
+        }
+    }
+    // searches the layouts that add the candidates to a layout, for ParIter's workers.
+    // a subtree's search is never saved, so its root keeps the start position.
+    pub(crate) fn subtree(gen: &'a Generator, crossword: Crossword, candidates: Arc<Vec<usize>>, seed: u64) -> Search<'a> {
+        Search {
+            gen,
+            seed,
+            stack: vec![Frame::new((0, START_POSITION), true, crossword, candidates, seed)],
+            counts_found: false
+        }
+    }
+
+    // replays the placements of the saved levels, then restores the filter
+    pub(crate) fn resume(gen: &'a Generator, checkpoint: &Checkpoint) -> Result<Search<'a>, CrosswordError> {
+        let mismatch = |reason: String| Err(CrosswordError::CheckpointMismatch { reason });
+        // the same seed visits the layouts in another order with another version
+        if checkpoint.rand_version != RAND_VERSION {
+            return mismatch(format!("it was saved with rand version {}, not {}", checkpoint.rand_version, RAND_VERSION))
+        }
+        if checkpoint.words.len() != gen.num_words() || checkpoint.words.iter().enumerate().any(|(i, word)| word != gen.word(i)) {
+            return mismatch("it was saved for a different word list".to_string())
+        }
+        if checkpoint.weights.iter().enumerate().any(|(i, &weight)| weight != gen.weight(i)) {
+            return mismatch("it was saved with different word weights".to_string())
+        }
+        if checkpoint.num_areas != gen.config().num_areas {
+            return mismatch(format!("it was saved with num_areas {}", checkpoint.num_areas))
+        }
+        if checkpoint.locked[..] != gen.locked()[..] {
+            return mismatch("it was saved with different locked words".to_string())
+        }
+        if checkpoint.score != gen.filter().score_name() {
+            return mismatch(format!("it was saved with score {}", checkpoint.score.as_ref().map_or("none", |score| &score[..])))
+        }
+        if checkpoint.anchor != gen.config().anchor {
+            return mismatch(format!("it was saved with anchor {}", checkpoint.anchor))
+        }
+        if checkpoint.dedup != gen.config().get_dedup() {
+            return mismatch(format!("it was saved with dedup {}", checkpoint.dedup))
+        }
+        if gen.config().seen != SeenSet::Exact {
+            return mismatch(format!("it can't be resumed with a {} seen set", gen.config().seen))
+        }
+        if checkpoint.size != gen.config().size {
+            return mismatch("it was saved with a different size limit".to_string())
+        }
+        if checkpoint.partial != gen.config().partial {
+            return mismatch(format!("it was saved {} partial", if checkpoint.partial { "with" } else { "without" }))
+        }
+        let mut stack: Vec<Frame> = vec![];
+        for (level, frame_state) in checkpoint.frames.iter().enumerate() {
+            let (word_index, pos) = frame_state.placement;
+            let mut frame = match stack.last() {
+                // anchors are only ever below the words placed on them
+                parent if frame_state.is_anchor => {
+                    if parent.is_some_and(|parent| !parent.is_anchor) {
+                        return mismatch(format!("level {} has an anchor above a placed word", level))
+                    }
+                    if !gen.anchors().contains(&word_index) {
+                        return mismatch(format!("level {} anchors at word {}, which isn't an anchor", level, word_index + 1))
+                    }
+                    let (crossword, candidates) = gen.get_init(word_index);
+                    Frame::new((word_index, START_POSITION), true, crossword, candidates, checkpoint.seed)
+                },
+                None => return mismatch("the first level has no anchor".to_string()),
+                Some(parent) => {
+                    let candidate_index = match parent.candidates.iter().position(|&i| i == word_index) {
+                        Some(candidate_index) => candidate_index,
+                        None => return mismatch(format!("level {} places word {} twice", level, word_index + 1))
+                    };
+                    let word = gen.word(word_index);
+                    let word_len = word.chars().count();
+                    if !pos.fits(word_len) {
+                        return mismatch(format!("level {} places word {} out of bounds", level, word_index + 1))
+                    }
+                    let mut candidates = (*parent.candidates).clone();
+                    candidates.remove(candidate_index);
+                    if !parent.crossword.can_add_word(word, word_len, pos) {
+                        return mismatch(format!("level {} places word {} where it collides with the layout", level, word_index + 1))
+                    }
+                    let crossword = parent.crossword.set(word, word_len, word_index, pos);
+                    if !crossword.is_valid() || crossword.num_overlaps() == parent.crossword.num_overlaps() {
+                        return mismatch(format!("level {} places word {} where it doesn't cross the layout", level, word_index + 1))
+                    }
+                    Frame::new((word_index, pos), false, crossword, Arc::new(candidates), checkpoint.seed)
+                }
+            };
+            if !frame.is_valid(gen, frame_state.cursor) {
+                return mismatch(format!("level {} has a cursor past the end of its words or letters", level))
+            }
+            frame.cursor = frame_state.cursor;
+            frame.has_children = frame_state.has_children;
+            stack.push(frame);
+        }
+        gen.tracker().start();
+        gen.filter().restore(checkpoint.filter.clone());
+        gen.set_next_seed(checkpoint.next_seed);
+        gen.set_best_partial(checkpoint.best_partial);
+        Ok(Search {
+            gen,
+            seed: checkpoint.seed,
+            stack,
+            counts_found: true
+        })
+    }
+
+    /// Only a search with an exact seen set can be saved, since fingerprints and
+    /// bloom filters can't give back the layouts they've seen.
+    pub fn checkpoint(&self) -> Result<Checkpoint, CrosswordError> {
+        if self.gen.config().seen != SeenSet::Exact {
+            return Err(CrosswordError::CheckpointUnsupported { reason: format!("it has a {} seen set", self.gen.config().seen) })
+        }
+        Ok(Checkpoint {
+            rand_version: RAND_VERSION,
+            words: (0..self.gen.num_words()).map(|i| self.gen.word(i).to_string()).collect(),
+            weights: (0..self.gen.num_words()).map(|i| self.gen.weight(i)).collect(),
+            num_areas: self.gen.config().num_areas,
+            anchor: self.gen.config().anchor,
+            dedup: self.gen.config().get_dedup(),
+            partial: self.gen.config().partial,
+            size: self.gen.config().size,
+            locked: self.gen.locked().to_vec(),
+            score: self.gen.filter().score_name(),
+            seed: self.seed,
+            next_seed: self.gen.peek_next_seed(),
+            best_partial: self.gen.best_partial(),
+            frames: self.stack.iter().map(|frame| FrameState {
+                placement: frame.placement,
+                is_anchor: frame.is_anchor,
+                cursor: frame.cursor,
+                has_children: frame.has_children
+            }).collect(),
+            filter: self.gen.filter().save()
+        })
+    }
+    // steps the search down to the next layout with at most split_at words left,
+    // and hands it over rather than searching it. Err is a crossword found above them.
+    fn next_subtree(&mut self, split_at: usize) -> Option<Result<Frame, Crossword>> {
+        loop {
+            if self.stack.last()?.candidates.len() <= split_at {
+                return self.stack.pop().map(Ok)
+            }
+            match self.step() {
+                Step::Found(crossword) => return Some(Err(crossword)),
+                Step::Searching => {},
+                Step::Done => return None
+            }
+        }
+    }
+
+    /// Whether every layout has been searched.
+    pub fn is_finished(&self) -> bool {
+        self.stack.is_empty()
+    }
+
+    /// Tries one candidate placement, or yields a finished crossword.
+    pub fn step(&mut self) -> Step {
+        let gen = self.gen;
+        let child = {
+            let frame = match self.stack.last_mut() {
+                Some(frame) => frame,
+                None => return Step::Done
+            };
+            if frame.candidates.is_empty() {
+                None
+            } else {
+                if !gen.tracker().check() {
+                    return Step::Done
+                }
+                match frame.next_placement(gen) {
+                    Some((w, letter, i2)) => {
+                        match gen.place(&frame.crossword, frame.bb, &frame.candidates, w, letter, i2) {
+                            Some((crossword, candidates)) => {
+                                let word_index = w.0;
+                                let pos = crossword.positions.get(word_index).unwrap();
+                                Some(Frame::new((word_index, pos), false, crossword, candidates, self.seed))
+                            },
+                            None => return Step::Searching
+                        }
+                    },
+                    None => None
+                }
+            }
+        };
+        match child {
+            Some(child) => {
+                self.stack.last_mut().unwrap().has_children = true;
+                self.stack.push(child);
+                Step::Searching
+            },
+            None => {
+                let frame = self.stack.pop().unwrap();
+                if !frame.is_anchor {
+                    gen.backtracked(&frame.crossword);
+                }
+                let crossword = if frame.has_children { None } else { gen.leaf(frame.crossword, &frame.candidates) };
+                match crossword {
+                    Some(crossword) => {
+                        if self.counts_found {
+                            gen.found(&crossword);
+                        }
+                        Step::Found(crossword)
+                    },
+                    None => Step::Searching
+                }
+            }
+        }
+    }
+}
+impl<'a> Iterator for Search<'a> {
+    type Item = Crossword;
+    fn next(&mut self) -> Option<Crossword> {
+        loop {
+            match self.step() {
+                Step::Found(crossword) => return Some(crossword),
+                Step::Searching => {},
+                Step::Done => return None
+            }
+        }
+    }
+}
+
+// multi_iter's searches: one goes down to the layouts with SPLIT_AT words left,
+// handing each to whichever of the others is free, which search below them. Frames
+// are handed over whole, so together they visit the layouts in the same way as
+// one search.
+pub(crate) struct MultiSearch<'a> {
+    split: Search<'a>,
+    searches: Vec<Search<'a>>,
+    // the search to take the next layout from
+    turn: usize
+}
+impl<'a> MultiSearch<'a> {
+    pub(crate) fn new(gen: &'a Generator, num_searches: usize) -> MultiSearch<'a> {
+        let split = Search { counts_found: false, ..Search::new(gen) };
+        let searches = (0..max(num_searches, 1)).map(|_| Search {
+            gen,
+            seed: split.seed,
+            stack: vec![],
+            counts_found: false
+        }).collect();
+        MultiSearch {
+            split,
+            searches,
+            turn: 0
+        }
+    }
+}
+impl<'a> Iterator for MultiSearch<'a> {
+    type Item = Crossword;
+    fn next(&mut self) -> Option<Crossword> {
+        let gen = self.split.gen;
+        // the searches in a row that are finished, with no layouts left to hand out
+        let mut num_idle = 0;
+        while num_idle < self.searches.len() && !gen.tracker().is_stopped() {
+            let search = &mut self.searches[self.turn];
+            let crossword = if search.is_finished() {
+                match self.split.next_subtree(SPLIT_AT) {
+                    Some(Ok(frame)) => {
+                        search.stack.push(frame);
+                        search.next()
+                    },
+                    Some(Err(crossword)) => Some(crossword),
+                    None => {
+                        num_idle += 1;
+                        self.turn = (self.turn + 1) % self.searches.len();
+                        continue
+                    }
+                }
+            } else {
+                search.next()
+            };
+            num_idle = 0;
+            if let Some(crossword) = crossword {
+                self.turn = (self.turn + 1) % self.searches.len();
+                gen.found(&crossword);
+                return Some(crossword)
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use config::Config;
+    use anchor::Anchor;
+    use placement::Direction::{Horizontal, Vertical};
+    use word_placements::Dedup;
+    use size::{SizeLimit, Orientation};
+    use word_list::Entry;
+
+    const WORDS: [&str; 8] = ["scent", "scarf", "fleet", "tenet", "eerie", "safe", "fret", "teas"];
+
+    fn make_generator() -> Generator {
+        with_anchor(Anchor::First)
+    }
+    fn with_anchor(anchor: Anchor) -> Generator {
+        Generator::with_config(&WORDS, Config { anchor, ..Config::new(1, 7) }).unwrap()
+    }
+
+    #[test]
+    fn multi_search() {
+        let expected: Vec<_> = make_generator().search().collect();
+        assert!(!expected.is_empty());
+        // one search over the split levels visits them in the same order
+        assert_eq!(expected, make_generator().multi_iter(1).collect::<Vec<_>>());
+        // the same layouts with more, without the area bound, which depends on the order
+        let words = ["toon", "took", "noob", "koob", "tent", "neck", "knot", "bonk"];
+        let all = || Generator::with_config(&words, Config::new(0, 7)).unwrap();
+        let mut expected: Vec<_> = all().search().map(|crossword| crossword.positions.to_string()).collect();
+        let mut positions: Vec<_> = all().multi_iter(3).map(|crossword| crossword.positions.to_string()).collect();
+        positions.sort();
+        expected.sort();
+        assert_eq!(expected, positions);
+    }
+
+    #[test]
+    fn resume() {
+        resume_with(&|| with_anchor(Anchor::First));
+        resume_with(&|| with_anchor(Anchor::Every));
+        resume_with(&|| make_generator().with_locked(&[("tenet", Position { row: 2, col: 1, dir: Vertical })]).unwrap());
+        resume_with(&|| Generator::with_config(&WORDS, Config { score: Some("area:2,perimeter".parse().unwrap()), ..Config::new(2, 7) }).unwrap());
+        resume_with(&|| Generator::with_config(&WORDS, Config { partial: true, ..Config::new(1, 7) }).unwrap());
+        let size = SizeLimit { max_width: Some(9), max_height: Some(7), exact: false, orientation: Orientation::Fixed };
+        resume_with(&|| Generator::with_config(&WORDS, Config { size, ..Config::new(1, 7) }).unwrap());
+    }
+    fn resume_with(make_generator: &dyn Fn() -> Generator) {
+        let expected: Vec<_> = make_generator().search().collect();
+        // save part way through, at a few points, and carry on with a new generator
+        for &num_steps in &[0, 1, 100, 2000, 100000] {
+            let gen = make_generator();
+            let mut search = gen.search();
+            let mut crosswords = vec![];
+            for _ in 0..num_steps {
+                match search.step() {
+                    Step::Found(crossword) => crosswords.push(crossword),
+                    Step::Searching => {},
+                    Step::Done => break
+                }
+            }
+            let text = search.checkpoint().unwrap().to_string();
+            let checkpoint = Checkpoint::parse("test", &text).unwrap();
+            assert_eq!(search.checkpoint().unwrap(), checkpoint);
+
+            let gen = make_generator();
+            crosswords.extend(gen.resume(&checkpoint).unwrap());
+            assert_eq!(expected, crosswords);
+        }
+    }
+
+    #[test]
+    fn mismatch() {
+        let gen = make_generator();
+        let checkpoint = gen.search().checkpoint().unwrap();
+        let other = Generator::with_config(&WORDS[1..], Config::new(1, 7)).unwrap();
+        assert!(other.resume(&checkpoint).is_err());
+        let other = Generator::with_config(&WORDS, Config::new(2, 7)).unwrap();
+        assert!(other.resume(&checkpoint).is_err());
+        let other = with_anchor(Anchor::Every);
+        assert!(other.resume(&checkpoint).is_err());
+        let other = make_generator().with_locked(&[("scent", START_POSITION)]).unwrap();
+        assert!(other.resume(&checkpoint).is_err());
+        let other = Generator::with_config(&WORDS, Config { score: Some("area".parse().unwrap()), ..Config::new(1, 7) }).unwrap();
+        assert!(other.resume(&checkpoint).is_err());
+        let other = Generator::with_config(&WORDS, Config { dedup: Some(Dedup::Canonical), ..Config::new(1, 7) }).unwrap();
+        assert!(other.resume(&checkpoint).is_err());
+        let other = Generator::with_config(&WORDS, Config { partial: true, ..Config::new(1, 7) }).unwrap();
+        assert!(other.resume(&checkpoint).is_err());
+        let size = SizeLimit { max_width: Some(9), ..SizeLimit::default() };
+        let other = Generator::with_config(&WORDS, Config { size, ..Config::new(1, 7) }).unwrap();
+        assert!(other.resume(&checkpoint).is_err());
+        let other = Generator::with_config(&WORDS, Config { seen: SeenSet::Fingerprint, ..Config::new(1, 7) }).unwrap();
+        assert!(other.resume(&checkpoint).is_err());
+        let mut entries: Vec<_> = WORDS.iter().map(|word| Entry::new(word)).collect();
+        entries[2].weight = 3;
+        let weighted = Generator::from_entries(&entries, Config::new(1, 7)).unwrap();
+        assert!(weighted.resume(&checkpoint).is_err());
+        assert!(weighted.resume(&weighted.search().checkpoint().unwrap()).is_ok());
+        let mut old = checkpoint.clone();
+        old.rand_version = 1;
+        assert!(make_generator().resume(&old).is_err());
+        match other.search().checkpoint() {
+            Err(CrosswordError::CheckpointUnsupported { .. }) => {},
+            other => panic!("expected CheckpointUnsupported, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn bad_cursor() {
+        let gen = make_generator();
+        let mut search = gen.search();
+        for _ in 0..100 {
+            search.step();
+        }
+        let checkpoint = search.checkpoint().unwrap();
+        assert!(make_generator().resume(&checkpoint).is_ok());
+        let num_levels = checkpoint.frames.len();
+        assert!(num_levels > 1);
+        let cursors = [
+            Cursor { letter: 99, ..checkpoint.frames[num_levels - 1].cursor },
+            Cursor { word_char: 99, ..checkpoint.frames[num_levels - 1].cursor },
+            Cursor { word: 99, letter: 0, word_char: 0 }
+        ];
+        for &cursor in &cursors {
+            let mut bad = checkpoint.clone();
+            bad.frames[num_levels - 1].cursor = cursor;
+            match make_generator().resume(&bad) {
+                Err(CrosswordError::CheckpointMismatch { .. }) => {},
+                other => panic!("expected a CheckpointMismatch, got {:?}", other.map(|_| ()))
+            }
+        }
+    }
+
+    #[test]
+    fn bad_placement() {
+        let gen = make_generator();
+        let mut search = gen.search();
+        for _ in 0..100 {
+            search.step();
+        }
+        let checkpoint = search.checkpoint().unwrap();
+        let (word_index, _) = checkpoint.frames[1].placement;
+        let mut bad = vec![checkpoint.clone(), checkpoint.clone(), checkpoint.clone()];
+        // on top of the anchor word, away from the layout, and anchored at a word
+        // that isn't the first
+        bad[0].frames[1].placement = (word_index, START_POSITION);
+        bad[1].frames[1].placement = (word_index, Position { row: 20, col: 20, dir: Horizontal });
+        bad[2].frames[0].placement.0 = 1;
+        for bad in &bad {
+            match make_generator().resume(bad) {
+                Err(CrosswordError::CheckpointMismatch { .. }) => {},
+                other => panic!("expected a CheckpointMismatch, got {:?}", other.map(|_| ()))
+            }
+        }
+    }
+}
@@ -0,0 +1,373 @@
+//! How a search remembers the layouts it has visited.
+
+use std::collections::HashSet;
+use std::mem::size_of;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+use word_placements::WordPlacements;
+use rand::hash;
+
+/// A bloom filter's false positive rate if the setting doesn't say.
+pub const DEFAULT_FALSE_POSITIVE_RATE: f64 = 0.001;
+/// The size of a bloom filter if `max_memory` doesn't say.
+pub const DEFAULT_BLOOM_BYTES: usize = 16 << 20;
+
+/// How a search remembers the layouts it has visited, so it doesn't visit them
+/// again.
+///
+/// Exact keeps every layout's placements. Fingerprint keeps a 64-bit hash of them
+/// instead, about a tenth of the memory for 8 words, and a bloom filter a few bits
+/// per layout. Both can mistake a new layout for one already seen, and skip it: a
+/// fingerprint almost never, a bloom filter at about its false positive rate.
+///
+/// With `Config::max_memory`, layouts stop being remembered once the seen set
+/// would grow past it, so they may be visited again (and yielded again, if they're
+/// finished). A bloom filter takes all of `max_memory`, and stops remembering
+/// layouts once more would push its false positive rate past the one asked for.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum SeenSet {
+    /// Every layout's placements.
+    #[default]
+    Exact,
+    /// A 64-bit hash of each layout's placements.
+    Fingerprint,
+    /// A bloom filter.
+    Bloom {
+        /// The chance of taking a new layout for one already seen.
+        false_positive_rate: f64
+    }
+}
+// for Config's Eq: parsing only takes rates strictly between 0 and 1, so a rate is
+// never NaN unless a caller writes one in by hand
+impl Eq for SeenSet {}
+
+// the seen set is split into shards with a lock each, so threads placing
+// different words rarely wait on each other
+const NUM_SHARDS: usize = 64;
+
+type Shards<T> = Vec<Mutex<HashSet<T>>>;
+
+fn new_shards<T: ::std::hash::Hash + Eq>() -> Shards<T> {
+    (0..NUM_SHARDS).map(|_| Mutex::new(HashSet::new())).collect()
+}
+// a hash set entry, taking the table's spare capacity and control bytes into account
+fn entry_bytes<T>(heap_bytes: usize) -> usize {
+    (size_of::<T>() + 1) * 8 / 7 + heap_bytes
+}
+
+// a bloom filter of bits set with fetch_or, so threads don't wait on each other.
+// two threads adding the same layout at once can both find it new.
+#[derive(Debug)]
+struct Bloom {
+    bits: Vec<AtomicU64>,
+    num_hashes: u64,
+    // the layouts it holds before its false positive rate goes past the one asked for
+    capacity: usize
+}
+impl Bloom {
+    fn new(num_bytes: usize, false_positive_rate: f64) -> Bloom {
+        let num_words = (num_bytes / 8).max(1);
+        let num_bits = (num_words * 64) as f64;
+        let ln2 = 2f64.ln();
+        Bloom {
+            bits: (0..num_words).map(|_| AtomicU64::new(0)).collect(),
+            num_hashes: (-false_positive_rate.log2()).round().max(1.0) as u64,
+            capacity: (num_bits * ln2 * ln2 / -false_positive_rate.ln()) as usize
+        }
+    }
+    fn num_bits(&self) -> u64 {
+        self.bits.len() as u64 * 64
+    }
+    // the bits of a layout, by double hashing
+    fn bit_indexes<'a>(&'a self, h1: u64, positions: &WordPlacements) -> impl Iterator<Item=u64> + 'a {
+        let h2 = hash(positions, 2) | 1;
+        let num_bits = self.num_bits();
+        (0..self.num_hashes).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % num_bits)
+    }
+    fn contains(&self, h1: u64, positions: &WordPlacements) -> bool {
+        self.bit_indexes(h1, positions).all(|i| self.bits[(i / 64) as usize].load(Ordering::Relaxed) & (1 << (i % 64)) != 0)
+    }
+    fn insert(&self, h1: u64, positions: &WordPlacements) {
+        for i in self.bit_indexes(h1, positions) {
+            self.bits[(i / 64) as usize].fetch_or(1 << (i % 64), Ordering::Relaxed);
+        }
+    }
+    fn clear(&self) {
+        for word in &self.bits {
+            word.store(0, Ordering::Relaxed);
+        }
+    }
+    fn false_positive_rate(&self, num_entries: usize) -> f64 {
+        let k = self.num_hashes as f64;
+        (1.0 - (-k * num_entries as f64 / self.num_bits() as f64).exp()).powf(k)
+    }
+}
+
+#[derive(Debug)]
+enum Store {
+    Exact(Shards<WordPlacements>),
+    Fingerprint(Shards<u64>),
+    Bloom(Bloom)
+}
+
+/// How much the seen set holds, and how much dedup it has given up.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct SeenStats {
+    /// Layouts remembered.
+    pub entries: usize,
+    /// Roughly the memory they take.
+    pub bytes: usize,
+    /// New layouts not remembered because of max_memory, which may be visited again.
+    pub dropped: usize,
+    /// The chance a new layout is taken for one already seen, and skipped.
+    pub false_positive_rate: f64
+}
+
+// the layouts visited so far, shared by every search of a generator
+#[derive(Debug)]
+pub(crate) struct Seen {
+    store: Store,
+    max_bytes: Option<usize>,
+    num_entries: AtomicUsize,
+    num_bytes: AtomicUsize,
+    num_dropped: AtomicUsize
+}
+impl Seen {
+    pub fn new(seen_set: SeenSet, max_memory: Option<usize>) -> Seen {
+        let store = match seen_set {
+            SeenSet::Exact => Store::Exact(new_shards()),
+            SeenSet::Fingerprint => Store::Fingerprint(new_shards()),
+            SeenSet::Bloom { false_positive_rate } => {
+                Store::Bloom(Bloom::new(max_memory.unwrap_or(DEFAULT_BLOOM_BYTES), false_positive_rate))
+            }
+        };
+        let num_bytes = match store {
+            Store::Bloom(ref bloom) => bloom.bits.len() * 8,
+            _ => 0
+        };
+        Seen {
+            store,
+            max_bytes: max_memory,
+            num_entries: AtomicUsize::new(0),
+            num_bytes: AtomicUsize::new(num_bytes),
+            num_dropped: AtomicUsize::new(0)
+        }
+    }
+
+    // whether there's room to remember another layout of this many bytes, counting
+    // it as dropped if not
+    fn has_room(&self, bytes: usize) -> bool {
+        let is_full = match self.store {
+            Store::Bloom(ref bloom) => self.num_entries.load(Ordering::Relaxed) >= bloom.capacity,
+            _ => self.max_bytes.is_some_and(|max_bytes| self.num_bytes.load(Ordering::Relaxed) + bytes > max_bytes)
+        };
+        if is_full {
+            self.num_dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        !is_full
+    }
+    fn add_entry(&self, bytes: usize) {
+        self.num_entries.fetch_add(1, Ordering::Relaxed);
+        self.num_bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Adds a layout, returning false if it has been seen before, or seems to have been.
+    pub fn insert(&self, positions: WordPlacements) -> bool {
+        let h = hash(&positions, 1);
+        let shard = (h % NUM_SHARDS as u64) as usize;
+        match self.store {
+            Store::Exact(ref shards) => {
+                let mut shard = shards[shard].lock().unwrap();
+                if shard.contains(&positions) {
+                    return false
+                }
+                let bytes = entry_bytes::<WordPlacements>(std::mem::size_of_val(positions.as_slice()));
+                if self.has_room(bytes) {
+                    shard.insert(positions);
+                    self.add_entry(bytes);
+                }
+            },
+            Store::Fingerprint(ref shards) => {
+                let mut shard = shards[shard].lock().unwrap();
+                if shard.contains(&h) {
+                    return false
+                }
+                let bytes = entry_bytes::<u64>(0);
+                if self.has_room(bytes) {
+                    shard.insert(h);
+                    self.add_entry(bytes);
+                }
+            },
+            Store::Bloom(ref bloom) => {
+                if bloom.contains(h, &positions) {
+                    return false
+                }
+                if self.has_room(0) {
+                    bloom.insert(h, &positions);
+                    self.add_entry(0);
+                }
+            }
+        }
+        true
+    }
+
+    /// The layouts remembered, which only an exact seen set can give back (see
+    /// `Search::checkpoint`).
+    pub fn save(&self) -> Vec<WordPlacements> {
+        match self.store {
+            Store::Exact(ref shards) => shards.iter().flat_map(|shard| shard.lock().unwrap().iter().cloned().collect::<Vec<_>>()).collect(),
+            _ => vec![]
+        }
+    }
+    /// Replaces the layouts remembered.
+    pub fn restore(&self, seen: Vec<WordPlacements>) {
+        match self.store {
+            Store::Exact(ref shards) => for shard in shards { shard.lock().unwrap().clear(); },
+            Store::Fingerprint(ref shards) => for shard in shards { shard.lock().unwrap().clear(); },
+            Store::Bloom(ref bloom) => bloom.clear()
+        }
+        let num_bytes = match self.store {
+            Store::Bloom(ref bloom) => bloom.bits.len() * 8,
+            _ => 0
+        };
+        self.num_entries.store(0, Ordering::Relaxed);
+        self.num_bytes.store(num_bytes, Ordering::Relaxed);
+        self.num_dropped.store(0, Ordering::Relaxed);
+        for positions in seen {
+            self.insert(positions);
+        }
+    }
+
+    pub fn stats(&self) -> SeenStats {
+        let entries = self.num_entries.load(Ordering::Relaxed);
+        SeenStats {
+            entries,
+            bytes: self.num_bytes.load(Ordering::Relaxed),
+            dropped: self.num_dropped.load(Ordering::Relaxed),
+            false_positive_rate: match self.store {
+                Store::Exact(_) => 0.0,
+                Store::Fingerprint(_) => entries as f64 / 2f64.powi(64),
+                Store::Bloom(ref bloom) => bloom.false_positive_rate(entries)
+            }
+        }
+    }
+}
+
+use std::fmt::{Display, Formatter, Result};
+impl Display for SeenSet {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match *self {
+            SeenSet::Exact => write!(f, "exact"),
+            SeenSet::Fingerprint => write!(f, "fingerprint"),
+            SeenSet::Bloom { false_positive_rate } => write!(f, "bloom:{}", false_positive_rate)
+        }
+    }
+}
+use std::str::FromStr;
+impl FromStr for SeenSet {
+    type Err = String;
+    // exact, fingerprint or bloom[:FALSE_POSITIVE_RATE]
+    fn from_str(s: &str) -> ::std::result::Result<SeenSet, String> {
+        let (name, value) = match s.find(':') {
+            Some(i) => (&s[..i], Some(&s[i + 1..])),
+            None => (s, None)
+        };
+        match (name, value) {
+            ("exact", None) => Ok(SeenSet::Exact),
+            ("fingerprint", None) => Ok(SeenSet::Fingerprint),
+            ("bloom", _) => {
+                let rate = match value {
+                    Some(value) => value.parse::<f64>().map_err(|e| format!("'{}': {}", value, e))?,
+                    None => DEFAULT_FALSE_POSITIVE_RATE
+                };
+                if !(rate > 0.0 && rate < 1.0) {
+                    return Err("the false positive rate must be between 0 and 1".to_string())
+                }
+                Ok(SeenSet::Bloom { false_positive_rate: rate })
+            },
+            _ => Err("expected exact, fingerprint or bloom[:FALSE_POSITIVE_RATE]".to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use placement::Position;
+    use placement::Direction::Horizontal;
+
+    // a different layout for each i
+    fn placements(i: usize) -> WordPlacements {
+        WordPlacements::new(2).set(0, Position { row: (i / 100) as i8, col: (i % 100) as i8, dir: Horizontal })
+    }
+
+    #[test]
+    fn insert() {
+        for &seen_set in &[SeenSet::Exact, SeenSet::Fingerprint, SeenSet::Bloom { false_positive_rate: 0.001 }] {
+            let seen = Seen::new(seen_set, Some(1 << 16));
+            assert!(seen.insert(placements(1)));
+            assert!(!seen.insert(placements(1)));
+            assert!(seen.insert(placements(2)));
+            let stats = seen.stats();
+            assert_eq!(2, stats.entries);
+            assert_eq!(0, stats.dropped);
+            assert!(stats.bytes > 0);
+            assert!(stats.false_positive_rate < 0.001);
+        }
+    }
+
+    #[test]
+    fn max_memory() {
+        let seen = Seen::new(SeenSet::Exact, Some(1000));
+        let num_new = (0..1000).filter(|&i| seen.insert(placements(i))).count();
+        // every layout is new, but the ones not remembered can come up again
+        assert_eq!(1000, num_new);
+        let stats = seen.stats();
+        assert!(stats.entries > 0 && stats.bytes <= 1000);
+        assert_eq!(1000, stats.entries + stats.dropped);
+        assert!(seen.insert(placements(999)));
+
+        // fingerprints take less memory, so more fit
+        let fingerprints = Seen::new(SeenSet::Fingerprint, Some(1000));
+        for i in 0..1000 {
+            fingerprints.insert(placements(i));
+        }
+        assert!(fingerprints.stats().entries > stats.entries);
+
+        // a bloom filter fills up to its false positive rate
+        let bloom = Seen::new(SeenSet::Bloom { false_positive_rate: 0.01 }, Some(64));
+        let num_new = (0..1000).filter(|&i| bloom.insert(placements(i))).count();
+        let stats = bloom.stats();
+        assert_eq!(64, stats.bytes);
+        assert!(stats.entries < 1000 && stats.dropped > 0);
+        assert_eq!(num_new, stats.entries + stats.dropped);
+        assert!(stats.false_positive_rate < 0.02);
+    }
+
+    #[test]
+    fn restore() {
+        let seen = Seen::new(SeenSet::Exact, None);
+        seen.insert(placements(1));
+        seen.insert(placements(2));
+        let saved = seen.save();
+        assert_eq!(2, saved.len());
+        let other = Seen::new(SeenSet::Exact, None);
+        other.insert(placements(3));
+        other.restore(saved);
+        assert_eq!(seen.stats(), other.stats());
+        assert!(!other.insert(placements(2)) && other.insert(placements(3)));
+    }
+
+    #[test]
+    fn parse() {
+        for seen_set in &[SeenSet::Exact, SeenSet::Fingerprint, SeenSet::Bloom { false_positive_rate: 0.05 }] {
+            assert_eq!(Ok(*seen_set), seen_set.to_string().parse());
+        }
+        assert_eq!(Ok(SeenSet::Bloom { false_positive_rate: DEFAULT_FALSE_POSITIVE_RATE }), "bloom".parse());
+        assert!("bloom:1".parse::<SeenSet>().is_err());
+        assert!("bloom:x".parse::<SeenSet>().is_err());
+        assert!("exact:1".parse::<SeenSet>().is_err());
+        assert!("cuckoo".parse::<SeenSet>().is_err());
+    }
+}
@@ -0,0 +1,102 @@
+//! Size limits a layout has to fit.
+
+use bounding_box::BoundingBox;
+
+/// Whether a layout can be turned on its side to fit a size limit.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Orientation {
+    /// Either way round, and printed portrait and landscape.
+    #[default]
+    Auto,
+    /// Width across and height down, as laid out.
+    Fixed
+}
+
+/// The frame a layout has to fit; None means unlimited.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct SizeLimit {
+    /// The most cols a layout can take.
+    pub max_width: Option<usize>,
+    /// The most rows a layout can take.
+    pub max_height: Option<usize>,
+    /// Finished layouts fill the frame exactly.
+    pub exact: bool,
+    /// Whether a layout can be turned on its side to fit.
+    pub orientation: Orientation
+}
+impl SizeLimit {
+    /// Whether there's a max width or height.
+    pub fn is_limited(&self) -> bool {
+        self.max_width.is_some() || self.max_height.is_some()
+    }
+    /// Whether a layout fits and fills the frame exactly when its transpose does.
+    pub fn is_symmetric(&self) -> bool {
+        self.orientation == Orientation::Auto || self.max_width == self.max_height
+    }
+    /// Whether a layout with this bounding box fits within the frame.
+    pub fn fits(&self, bb: BoundingBox) -> bool {
+        let within = |size: i16, max_size: Option<usize>| max_size.is_none_or(|max_size| size as usize <= max_size);
+        let (width, height) = (bb.width(), bb.height());
+        (within(width, self.max_width) && within(height, self.max_height)) ||
+            (self.orientation == Orientation::Auto && within(height, self.max_width) && within(width, self.max_height))
+    }
+    /// Whether a finished layout is big enough, if it has to fill the frame.
+    pub fn fills(&self, bb: BoundingBox) -> bool {
+        if !self.exact {
+            return true
+        }
+        let equal = |size: i16, max_size: Option<usize>| max_size.is_none_or(|max_size| size as usize == max_size);
+        let (width, height) = (bb.width(), bb.height());
+        (equal(width, self.max_width) && equal(height, self.max_height)) ||
+            (self.orientation == Orientation::Auto && equal(height, self.max_width) && equal(width, self.max_height))
+    }
+}
+
+use std::fmt::{Display, Formatter, Result};
+impl Display for Orientation {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{}", match *self {
+            Orientation::Auto => "auto",
+            Orientation::Fixed => "fixed"
+        })
+    }
+}
+use std::str::FromStr;
+impl FromStr for Orientation {
+    type Err = String;
+    fn from_str(s: &str) -> ::std::result::Result<Orientation, String> {
+        match s {
+            "auto" => Ok(Orientation::Auto),
+            "fixed" => Ok(Orientation::Fixed),
+            _ => Err("expected auto or fixed".to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fits() {
+        let limit = SizeLimit { max_width: Some(5), max_height: Some(3), ..SizeLimit::default() };
+        let wide = BoundingBox::new(0, 0, 2, 4);
+        let tall = BoundingBox::new(0, 0, 4, 2);
+        assert!(limit.fits(wide) && limit.fits(tall));
+        assert!(!limit.fits(BoundingBox::new(0, 0, 3, 3)));
+        let fixed = SizeLimit { orientation: Orientation::Fixed, ..limit };
+        assert!(fixed.fits(wide) && !fixed.fits(tall));
+        assert!(SizeLimit { max_width: Some(5), ..SizeLimit::default() }.fits(BoundingBox::new(0, 0, 40, 4)));
+        assert!(limit.is_symmetric() && !fixed.is_symmetric());
+        assert!(SizeLimit { max_width: Some(3), ..fixed }.is_symmetric());
+    }
+
+    #[test]
+    fn fills() {
+        let limit = SizeLimit { max_width: Some(5), max_height: Some(3), exact: true, ..SizeLimit::default() };
+        assert!(limit.fills(BoundingBox::new(0, 0, 4, 2)));
+        assert!(!limit.fills(BoundingBox::new(0, 0, 2, 3)));
+        assert!(!SizeLimit { orientation: Orientation::Fixed, ..limit }.fills(BoundingBox::new(0, 0, 4, 2)));
+        assert!(SizeLimit::default().fills(BoundingBox::new(0, 0, 2, 3)));
+    }
+}
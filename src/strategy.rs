@@ -0,0 +1,292 @@
+//! The orders a search can visit layouts in.
+
+use std::sync::Arc;
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+
+use crossword::Crossword;
+use generate::Generator;
+
+/// The beam width if `beam` doesn't say.
+pub const DEFAULT_BEAM_WIDTH: usize = 16;
+/// The discrepancies allowed if `lds` doesn't say.
+pub const DEFAULT_DISCREPANCIES: usize = 2;
+
+/// The order a generator's search visits layouts in.
+///
+/// Beam and limited-discrepancy search rank the layouts at each step by the
+/// generator's score, or else by smallest area then most overlaps, so they find
+/// good layouts early. Neither visits every layout, unless it's wide or lenient
+/// enough.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Strategy {
+    /// Depth-first, in the seed's order, visiting every layout.
+    #[default]
+    Dfs,
+    /// Adds a word to every layout in the beam, then keeps the best of them.
+    Beam {
+        /// How many layouts the beam keeps.
+        width: usize
+    },
+    /// Depth-first from the best layout at each step, but going the i-th best way
+    /// counts as i discrepancies. The paths with fewer discrepancies are followed
+    /// first.
+    Lds {
+        /// The most discrepancies a path can have.
+        discrepancies: usize
+    }
+}
+
+type State = (Crossword, Arc<Vec<usize>>);
+
+// lower is better: the score, then the area, then more overlaps
+fn rank(gen: &Generator, crossword: &Crossword) -> (f64, i16, i8) {
+    (gen.score(crossword).unwrap_or(0.0), crossword.bounding_box().area(), -crossword.num_overlaps())
+}
+// best first, keeping the search order for ties
+fn sort_by_rank<T>(gen: &Generator, children: &mut Vec<(Crossword, T)>) {
+    let mut ranked: Vec<_> = children.drain(..).map(|child| (rank(gen, &child.0), child)).collect();
+    ranked.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+    children.extend(ranked.into_iter().map(|(_, child)| child));
+}
+
+pub(crate) struct Beam<'a> {
+    gen: &'a Generator,
+    seed: u64,
+    width: usize,
+    beam: Vec<State>,
+    found: VecDeque<Crossword>,
+    // dead ends, held back until the beam runs out in case a later level places more
+    partials: Vec<Crossword>
+}
+impl<'a> Beam<'a> {
+    pub(crate) fn new(gen: &'a Generator, width: usize) -> Beam<'a> {
+        gen.tracker().start();
+        Beam {
+            gen,
+            seed: gen.get_seed(),
+            width,
+            beam: gen.get_inits(),
+            found: VecDeque::new(),
+            partials: vec![]
+        }
+    }
+    // every layout in the beam has the same number of words left to place. only
+    // the children that make the beam are committed.
+    fn next_level(&mut self) {
+        let mut children = vec![];
+        for (crossword, candidates) in self.beam.drain(..) {
+            if candidates.is_empty() {
+                self.found.push_back(crossword);
+                continue
+            }
+            let num_children = children.len();
+            let uncommitted = self.gen.uncommitted_children(crossword.clone(), candidates.clone(), self.seed);
+            children.extend(uncommitted.map(|(child, w)| (child, (w, candidates.clone()))));
+            if self.gen.config().partial && children.len() == num_children {
+                self.partials.extend(self.gen.partial_solution(crossword, &candidates));
+            }
+        }
+        sort_by_rank(self.gen, &mut children);
+        let gen = self.gen;
+        self.beam = children.into_iter()
+            .filter_map(|(child, (w, candidates))| gen.commit(child, &candidates, w))
+            .take(self.width)
+            .collect();
+    }
+}
+impl<'a> Iterator for Beam<'a> {
+    type Item = Crossword;
+    fn next(&mut self) -> Option<Crossword> {
+        loop {
+            if let Some(crossword) = self.found.pop_front() {
+                self.gen.found(&crossword);
+                return Some(crossword)
+            }
+            if self.gen.tracker().is_stopped() {
+                return None
+            }
+            if self.beam.is_empty() {
+                let best = self.gen.best_partial();
+                let gen = self.gen;
+                let partials = self.partials.drain(..).filter(|crossword| gen.placed_weight(crossword) >= best);
+                self.found.extend(partials);
+                if self.found.is_empty() {
+                    return None
+                }
+                continue
+            }
+            self.next_level();
+        }
+    }
+}
+
+pub(crate) struct Lds<'a> {
+    gen: &'a Generator,
+    seed: u64,
+    // the layouts still to visit by the number of discrepancies to reach them, each
+    // a stack so the best is on top
+    open: Vec<Vec<State>>
+}
+impl<'a> Lds<'a> {
+    pub(crate) fn new(gen: &'a Generator, discrepancies: usize) -> Lds<'a> {
+        gen.tracker().start();
+        let mut open = vec![vec![]; discrepancies + 1];
+        open[0] = gen.get_inits().into_iter().rev().collect();
+        Lds {
+            gen,
+            seed: gen.get_seed(),
+            open
+        }
+    }
+}
+impl<'a> Iterator for Lds<'a> {
+    type Item = Crossword;
+    fn next(&mut self) -> Option<Crossword> {
+        loop {
+            if self.gen.tracker().is_stopped() {
+                return None
+            }
+            let discrepancies = self.open.iter().position(|states| !states.is_empty())?;
+            let (crossword, candidates) = self.open[discrepancies].pop().unwrap();
+            let mut children: Vec<_> = self.gen.uncommitted_children(crossword.clone(), candidates.clone(), self.seed).collect();
+            let crossword = if children.is_empty() {
+                self.gen.leaf(crossword, &candidates)
+            } else {
+                sort_by_rank(self.gen, &mut children);
+                // the i-th best child kept costs i discrepancies, so only those within
+                // the limit are committed
+                let gen = self.gen;
+                let kept: Vec<State> = children.into_iter()
+                    .filter_map(|(child, w)| gen.commit(child, &candidates, w))
+                    .take(self.open.len() - discrepancies)
+                    .collect();
+                for (i, child) in kept.into_iter().enumerate().rev() {
+                    self.open[discrepancies + i].push(child);
+                }
+                None
+            };
+            if let Some(crossword) = crossword {
+                self.gen.found(&crossword);
+                return Some(crossword)
+            }
+        }
+    }
+}
+
+use std::fmt::{Display, Formatter, Result};
+impl Display for Strategy {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match *self {
+            Strategy::Dfs => write!(f, "dfs"),
+            Strategy::Beam { width } => write!(f, "beam:{}", width),
+            Strategy::Lds { discrepancies } => write!(f, "lds:{}", discrepancies)
+        }
+    }
+}
+use std::str::FromStr;
+impl FromStr for Strategy {
+    type Err = String;
+    // dfs, beam[:WIDTH] or lds[:DISCREPANCIES]
+    fn from_str(s: &str) -> ::std::result::Result<Strategy, String> {
+        let (name, value) = match s.find(':') {
+            Some(i) => (&s[..i], Some(&s[i + 1..])),
+            None => (s, None)
+        };
+        let parse = |default: usize| match value {
+            Some(value) => value.parse::<usize>().map_err(|e| format!("'{}': {}", value, e)),
+            None => Ok(default)
+        };
+        match name {
+            "dfs" if value.is_none() => Ok(Strategy::Dfs),
+            "beam" => match parse(DEFAULT_BEAM_WIDTH)? {
+                0 => Err("the beam width must be at least 1".to_string()),
+                width => Ok(Strategy::Beam { width })
+            },
+            "lds" => Ok(Strategy::Lds { discrepancies: parse(DEFAULT_DISCREPANCIES)? }),
+            _ => Err("expected dfs, beam[:WIDTH] or lds[:DISCREPANCIES]".to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use config::Config;
+
+    const WORDS: [&str; 4] = ["toon", "took", "noob", "koob"];
+    // enough words that most layouts are reached more than one way
+    const MORE_WORDS: [&str; 6] = ["toon", "took", "noob", "koob", "bonk", "knot"];
+
+    fn crosswords(strategy: Strategy) -> Vec<Crossword> {
+        crosswords_of(&WORDS, strategy)
+    }
+    fn crosswords_of(words: &[&str], strategy: Strategy) -> Vec<Crossword> {
+        let config = Config { strategy, ..Config::default() };
+        let gen = Generator::with_config(words, config).unwrap();
+        let crosswords: Vec<_> = gen.iter().collect();
+        assert!(crosswords.iter().all(|crossword| crossword.is_valid()));
+        crosswords
+    }
+    fn sorted(crosswords: Vec<Crossword>) -> Vec<String> {
+        let mut placements: Vec<_> = crosswords.iter().map(|crossword| crossword.positions.to_string()).collect();
+        placements.sort();
+        placements
+    }
+
+    #[test]
+    fn beam() {
+        let all = crosswords(Strategy::Dfs);
+        assert_eq!(22, all.len());
+        assert_eq!(sorted(all.clone()), sorted(crosswords(Strategy::Beam { width: 1000 })));
+        let narrow = crosswords(Strategy::Beam { width: 1 });
+        assert_eq!(1, narrow.len());
+        let min_area = all.iter().map(|crossword| crossword.bounding_box().area()).min();
+        assert_eq!(min_area, narrow.iter().map(|crossword| crossword.bounding_box().area()).min());
+    }
+
+    #[test]
+    fn lds() {
+        let all = crosswords(Strategy::Dfs);
+        assert_eq!(sorted(all), sorted(crosswords(Strategy::Lds { discrepancies: 100 })));
+        // straight down the best path
+        let greedy = crosswords(Strategy::Lds { discrepancies: 0 });
+        assert_eq!(1, greedy.len());
+        let more = crosswords(Strategy::Lds { discrepancies: 2 });
+        assert!(more.len() > 1 && more.len() < 22);
+        assert_eq!(greedy[0], more[0]);
+    }
+
+    #[test]
+    fn lds_finds_what_dfs_finds() {
+        let all = crosswords_of(&MORE_WORDS, Strategy::Dfs);
+        assert_eq!(564, all.len());
+        assert_eq!(sorted(all), sorted(crosswords_of(&MORE_WORDS, Strategy::Lds { discrepancies: 1000 })));
+    }
+
+    #[test]
+    fn only_kept_children_count() {
+        // a single path adds at most one word per step, and the children left
+        // behind aren't counted or marked as seen
+        for strategy in &[Strategy::Beam { width: 1 }, Strategy::Lds { discrepancies: 0 }] {
+            let config = Config { strategy: *strategy, ..Config::default() };
+            let gen = Generator::with_config(&MORE_WORDS, config).unwrap();
+            gen.iter().count();
+            let stats = gen.stats();
+            assert!(stats.nodes < MORE_WORDS.len(), "{}: {} nodes", strategy, stats.nodes);
+            assert_eq!(0, stats.seen_rejections);
+        }
+    }
+
+    #[test]
+    fn parse() {
+        for strategy in &[Strategy::Dfs, Strategy::Beam { width: 3 }, Strategy::Lds { discrepancies: 0 }] {
+            assert_eq!(Ok(*strategy), strategy.to_string().parse());
+        }
+        assert_eq!(Ok(Strategy::Beam { width: DEFAULT_BEAM_WIDTH }), "beam".parse());
+        assert_eq!(Ok(Strategy::Lds { discrepancies: DEFAULT_DISCREPANCIES }), "lds".parse());
+        assert!("beam:0".parse::<Strategy>().is_err());
+        assert!("dfs:1".parse::<Strategy>().is_err());
+        assert!("bfs".parse::<Strategy>().is_err());
+    }
+}
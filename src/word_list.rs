@@ -0,0 +1,185 @@
+//! Word list entries and their lines.
+
+use error::CrosswordError;
+use placement::MAX_WORD_LEN;
+
+// the weight of a word whose entry doesn't give one
+pub(crate) const DEFAULT_WEIGHT: u32 = 1;
+/// A crossword entry needs at least two letters.
+pub const MIN_WORD_LEN: usize = 2;
+
+/// One line of a word list: `word<TAB>clue<TAB>weight`, clue and weight optional.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// The word to lay out.
+    pub word: String,
+    /// The clue printed with it, if any.
+    pub clue: Option<String>,
+    /// How much placing the word is worth, in partial mode; 1 unless given.
+    pub weight: u32
+}
+impl Entry {
+    /// An entry for the word with no clue and a weight of 1.
+    pub fn new(word: &str) -> Entry {
+        Entry {
+            word: word.to_string(),
+            clue: None,
+            weight: DEFAULT_WEIGHT
+        }
+    }
+}
+
+/// Why a word list couldn't be read. `source` is the path it was read from, or
+/// `<stdin>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WordListError {
+    /// It couldn't be read.
+    Io {
+        /// The path.
+        source: String,
+        /// The IO error.
+        reason: String
+    },
+    /// A line isn't valid UTF-8, and it wasn't read lossily.
+    InvalidUtf8 {
+        /// The path.
+        source: String,
+        /// The line number, from 1.
+        line: usize,
+        /// The byte offset in the line of the first invalid byte, from 1.
+        column: usize
+    },
+    /// A line has more than the word, clue and weight fields.
+    TooManyFields {
+        /// The path.
+        source: String,
+        /// The line number, from 1.
+        line: usize,
+        /// How many tab-separated fields it has.
+        fields: usize
+    },
+    /// A weight isn't a whole number.
+    InvalidWeight {
+        /// The path.
+        source: String,
+        /// The line number, from 1.
+        line: usize,
+        /// The weight field.
+        value: String,
+        /// Why it couldn't be parsed.
+        reason: String
+    }
+}
+use std::fmt::{Display, Formatter, Result as FmtResult};
+impl Display for WordListError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match *self {
+            WordListError::Io { ref source, ref reason } => {
+                write!(f, "{}: {}", source, reason)
+            },
+            WordListError::InvalidUtf8 { ref source, line, column } => {
+                write!(f, "{}:{}:{}: invalid UTF-8 (use --lossy to replace it)", source, line, column)
+            },
+            WordListError::TooManyFields { ref source, line, fields } => {
+                write!(f, "{}:{}: expected at most 3 tab-separated fields (word, clue, weight) but got {}", source, line, fields)
+            },
+            WordListError::InvalidWeight { ref source, line, ref value, ref reason } => {
+                write!(f, "{}:{}: invalid weight '{}': {}", source, line, value, reason)
+            }
+        }
+    }
+}
+
+// rejects word lists the generator can't lay out at all
+pub(crate) fn check_words<S: AsRef<str>>(words: &[S]) -> Result<(), CrosswordError> {
+    if words.is_empty() {
+        return Err(CrosswordError::EmptyWordList)
+    }
+    for (index, word) in words.iter().enumerate() {
+        let word = word.as_ref();
+        let word_len = word.chars().count();
+        if word_len < MIN_WORD_LEN {
+            return Err(CrosswordError::WordTooShort { index, word: word.to_string() })
+        }
+        if word_len > MAX_WORD_LEN {
+            return Err(CrosswordError::WordTooLong { index, word: word.to_string() })
+        }
+    }
+    Ok(())
+}
+
+/// Parses one line, returning None for blank lines and # comments.
+pub fn parse_line(source: &str, line_number: usize, line: &str) -> Result<Option<Entry>, WordListError> {
+    if line.trim().is_empty() || line.trim_start().starts_with('#') {
+        return Ok(None)
+    }
+    let fields: Vec<&str> = line.split('\t').collect();
+    if fields.len() > 3 {
+        return Err(WordListError::TooManyFields {
+            source: source.to_string(),
+            line: line_number,
+            fields: fields.len()
+        })
+    }
+    let word = fields[0].trim();
+    let clue = fields.get(1)
+        .map(|clue| clue.trim())
+        .and_then(|clue| if clue.is_empty() { None } else { Some(clue.to_string()) });
+    let weight = match fields.get(2).map(|weight| weight.trim()) {
+        Some(weight) if !weight.is_empty() => {
+            weight.parse::<u32>().map_err(|e| WordListError::InvalidWeight {
+                source: source.to_string(),
+                line: line_number,
+                value: weight.to_string(),
+                reason: e.to_string()
+            })?
+        },
+        _ => DEFAULT_WEIGHT
+    };
+    Ok(Some(Entry {
+        word: word.to_string(),
+        clue,
+        weight
+    }))
+}
+
+/// Parses a word list. `source` names the input in error messages, and lines
+/// are numbered from 1.
+pub fn parse(source: &str, text: &str) -> Result<Vec<Entry>, WordListError> {
+    let mut entries = vec![];
+    for (i, line) in text.lines().enumerate() {
+        if let Some(entry) = parse_line(source, i + 1, line)? {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_fields() {
+        let text = "# theme: greetings\nhello\ta greeting\t3\n\nworld\t\t2\n  # indented comment\nrust\tan alloy\n";
+        let entries = parse("test", text).unwrap();
+        assert_eq!(vec![
+            Entry { word: "hello".to_string(), clue: Some("a greeting".to_string()), weight: 3 },
+            Entry { word: "world".to_string(), clue: None, weight: 2 },
+            Entry { word: "rust".to_string(), clue: Some("an alloy".to_string()), weight: DEFAULT_WEIGHT }
+        ], entries);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(Err(WordListError::TooManyFields { source: "test".to_string(), line: 2, fields: 4 }),
+            parse("test", "ok\na\tb\tc\td"));
+        match parse("test", "hello\tclue\theavy") {
+            Err(WordListError::InvalidWeight { line, value, .. }) => {
+                assert_eq!(1, line);
+                assert_eq!("heavy", value);
+            },
+            other => panic!("expected InvalidWeight, got {:?}", other)
+        }
+    }
+}
@@ -1,30 +1,117 @@
+//! The placements of a layout's words, and how layouts are deduplicated.
+
 use placement::Position;
+use placement::Direction::{Horizontal, Vertical};
 
+/// The position of each word of the word list, if it has been placed.
 #[derive(Debug, Eq, PartialEq, Clone, Hash)]
 pub struct WordPlacements(Vec<Option<Position>>);
 
 impl WordPlacements {
+    /// The placements of `n` words, none of them placed yet.
     pub fn new(n: usize) -> WordPlacements {
         WordPlacements(vec![None; n])
     }
-    // immutable set: returns a new copy
+    /// A copy with the word at `word_index` placed at `pos`.
     pub fn set(&self, word_index: usize, pos: Position) -> WordPlacements {
         let mut next_self = self.clone();
         next_self.0[word_index] = Some(pos);
         next_self
     }
+    /// Where the word at `word_index` is placed, if it is.
+    pub fn get(&self, word_index: usize) -> Option<Position> {
+        self.0[word_index]
+    }
+    /// The placements of each word, by word index.
+    pub fn from_vec(placements: Vec<Option<Position>>) -> WordPlacements {
+        WordPlacements(placements)
+    }
+    /// The placement of each word, by word index.
+    pub fn as_slice(&self) -> &[Option<Position>] {
+        &self.0
+    }
+
+    /// The placements moved so the placed words start from row 0 and col 0, or
+    /// its transpose if that sorts first, so layouts that differ only by those
+    /// compare equal.
+    pub fn canonical(&self) -> WordPlacements {
+        let transposed = WordPlacements(self.0.iter().map(|opt_pos| opt_pos.map(|pos| Position {
+            row: pos.col,
+            col: pos.row,
+            dir: match pos.dir { Horizontal => Vertical, Vertical => Horizontal }
+        })).collect());
+        let (placements, transposed) = (self.translated(), transposed.translated());
+        if placements.sort_key() <= transposed.sort_key() { placements } else { transposed }
+    }
+    /// The placements moved so the placed words start from row 0 and col 0.
+    pub fn translated(&self) -> WordPlacements {
+        // words only go right or down, so the top left is the smallest start row and col
+        let top = self.0.iter().filter_map(|&opt_pos| opt_pos.map(|pos| pos.row)).min().unwrap_or(0);
+        let left = self.0.iter().filter_map(|&opt_pos| opt_pos.map(|pos| pos.col)).min().unwrap_or(0);
+        WordPlacements(self.0.iter().map(|opt_pos| opt_pos.map(|pos| Position {
+            row: pos.row - top,
+            col: pos.col - left,
+            dir: pos.dir
+        })).collect())
+    }
+    fn sort_key(&self) -> Vec<Option<(i8, i8, bool)>> {
+        self.0.iter().map(|opt_pos| opt_pos.map(|pos| (pos.row, pos.col, pos.dir == Vertical))).collect()
+    }
+}
+
+/// How a search tells whether it has seen a layout before.
+///
+/// Canonical counts layouts that only differ by where they are or by being
+/// transposed as the same. Under a fixed orientation with a frame that isn't
+/// square, a layout may fit where its transpose doesn't, so only moved layouts
+/// are. Rotated or mirrored layouts spell their words backwards, so they're never
+/// the same layout and aren't considered.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Dedup {
+    /// Every word in the same position.
+    Exact,
+    /// The same canonical placements.
+    Canonical
+}
+impl Dedup {
+    /// What two layouts that are the same have in common.
+    pub fn key(&self, placements: &WordPlacements) -> WordPlacements {
+        match *self {
+            Dedup::Exact => placements.clone(),
+            Dedup::Canonical => placements.canonical()
+        }
+    }
 }
 
 use std::fmt::{Display, Formatter, Result};
+impl Display for Dedup {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{}", match *self {
+            Dedup::Exact => "exact",
+            Dedup::Canonical => "canonical"
+        })
+    }
+}
+use std::str::FromStr;
+impl FromStr for Dedup {
+    type Err = String;
+    fn from_str(s: &str) -> ::std::result::Result<Dedup, String> {
+        match s {
+            "exact" => Ok(Dedup::Exact),
+            "canonical" => Ok(Dedup::Canonical),
+            _ => Err("expected exact or canonical".to_string())
+        }
+    }
+}
+
 impl Display for WordPlacements {
     fn fmt(&self, f: &mut Formatter) -> Result {
-        for (i, &opt_pos) in self.0.iter().enumerate() {
-            if let Some(pos) = opt_pos {
-                write!(f, "{}", pos)?;
-                if i < self.0.len() - 1 {
-                    write!(f, ",")?;
-                }
+        // only the placed words, which is every word unless it's a partial solution
+        for (i, pos) in self.0.iter().filter_map(|&opt_pos| opt_pos).enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
             }
+            write!(f, "{}", pos)?;
         }
         write!(f, "")
     }
@@ -32,8 +119,6 @@ impl Display for WordPlacements {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use placement::Position;
-    use placement::Direction::{ Horizontal, Vertical };
 
     #[test]
     fn new() {
@@ -62,4 +147,32 @@ mod tests {
         set.insert(wp1.clone());
         assert!(set.contains(&wp2));
     }
+
+    #[test]
+    fn display() {
+        let pos = Position { row: 1, col: -1, dir: Vertical };
+        assert_eq!("0,0,H,1,-1,V", format!("{}", WordPlacements(vec![Some(Position { row: 0, col: 0, dir: Horizontal }), Some(pos)])));
+        assert_eq!("1,-1,V", format!("{}", WordPlacements(vec![Some(pos), None])));
+    }
+
+    #[test]
+    fn canonical() {
+        let wp = WordPlacements(vec![
+            Some(Position { row: 0, col: 0, dir: Horizontal }),
+            Some(Position { row: -2, col: 1, dir: Vertical })
+        ]);
+        let transposed = WordPlacements(vec![
+            Some(Position { row: 5, col: 3, dir: Vertical }),
+            Some(Position { row: 6, col: 1, dir: Horizontal })
+        ]);
+        assert_eq!(wp.canonical(), transposed.canonical());
+        assert_eq!("0,2,V,1,0,H", format!("{}", wp.canonical()));
+        assert_ne!(wp.canonical(), WordPlacements::new(2).set(0, Position { row: 0, col: 0, dir: Horizontal }).canonical());
+        assert_eq!(Dedup::Canonical.key(&wp), Dedup::Canonical.key(&transposed));
+        assert_ne!(Dedup::Exact.key(&wp), Dedup::Exact.key(&transposed));
+        for dedup in &[Dedup::Exact, Dedup::Canonical] {
+            assert_eq!(Ok(*dedup), dedup.to_string().parse());
+        }
+        assert!("rotated".parse::<Dedup>().is_err());
+    }
 }